@@ -0,0 +1,332 @@
+use std::fs::{self,File};
+use std::io::{BufReader,Read};
+use std::time::UNIX_EPOCH;
+use crate::graph::*;
+
+//File layout, all fixed width integers little endian:
+//  magic (8 bytes) | version (u32) | payload length (u64) | FNV-1a checksum of the payload (u64) | payload
+//The payload holds LEB128 varints and length-prefixed UTF-8 strings:
+//  source count, then per source: canonical path, modified secs, modified nanos, size, content hash (u64, 0 unless
+//  written for ContentHash validation)
+//  n, then per mapped node: original id, label, subject, feature encoding tag, feature count, feature bytes
+//  then per mapped node: out-degree followed by the cited mapped nodes
+const MAGIC: &[u8;8] = b"CITGRAPH";
+pub const CACHE_VERSION: u32 = 1;
+const HEADER_LEN: usize = 8 + 4 + 8 + 8;
+//Features stored one byte each, or eight 0/1 features per byte
+const FEATURES_RAW: u8 = 0;
+const FEATURES_BITS: u8 = 1;
+
+//How a cache decides that the files it was built from have changed
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum CacheValidation{
+    //Modification time and size, cheap but fooled by a touch or a copy
+    ModifiedTime,
+    //Hash of the full file contents
+    ContentHash
+}
+
+//What a source file looked like when the cache was written
+#[derive(Debug,Clone,PartialEq,Eq)]
+struct SourceFingerprint{
+    path:String,
+    modified_secs:u64,
+    modified_nanos:u32,
+    size:u64,
+    hash:u64
+}
+
+impl SourceFingerprint{
+    fn of(path:&Path, with_hash:bool) -> Result<Self,String>{
+        let metadata = fs::metadata(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        //Canonical so that ./edges.csv and an absolute path to the same file are the same source
+        let canonical = fs::canonicalize(path).map_err(|e| format!("Could not resolve {}: {}", path.display(), e))?;
+        Ok(SourceFingerprint{
+            path:canonical.to_string_lossy().into_owned(),
+            modified_secs:modified.as_secs(),
+            modified_nanos:modified.subsec_nanos(),
+            size:metadata.len(),
+            hash:if with_hash { hash_file(path)? } else { 0 }
+        })
+    }
+
+    fn matches(&self, path:&Path, validation:CacheValidation) -> Result<bool,String>{
+        let current = SourceFingerprint::of(path, validation == CacheValidation::ContentHash)?;
+        if current.path != self.path{
+            return Ok(false);
+        }
+        Ok(match validation{
+            CacheValidation::ModifiedTime => (current.modified_secs, current.modified_nanos, current.size) == (self.modified_secs, self.modified_nanos, self.size),
+            CacheValidation::ContentHash => current.size == self.size && current.hash == self.hash
+        })
+    }
+}
+
+impl Graph{
+    //Write the graph in the binary cache format. `sources` are the files it was read from, so a later
+    //load_binary_if_fresh can tell when they change; pass an empty slice for a cache that never goes stale.
+    //Sources are only hashed for ContentHash validation, which is then the only way such a cache stays fresh.
+    pub fn save_binary(&self, path:impl AsRef<Path>, sources:&[&Path], validation:CacheValidation) -> Result<(), Box<dyn std::error::Error>>{
+        let fingerprints = fingerprint_all(sources, validation)?;
+        self.write_cache(path.as_ref(), &fingerprints)
+    }
+
+    //The cache is written to a temporary file next to `path` and renamed over it, so a reader that has the old
+    //file memory mapped keeps its own copy instead of seeing it truncated
+    fn write_cache(&self, path:&Path, fingerprints:&[SourceFingerprint]) -> Result<(), Box<dyn std::error::Error>>{
+        let mut payload = Vec::new();
+        write_varint(&mut payload, fingerprints.len() as u64);
+        for fingerprint in fingerprints.iter(){
+            write_string(&mut payload, &fingerprint.path);
+            write_varint(&mut payload, fingerprint.modified_secs);
+            write_varint(&mut payload, fingerprint.modified_nanos as u64);
+            write_varint(&mut payload, fingerprint.size);
+            payload.extend_from_slice(&fingerprint.hash.to_le_bytes());
+        }
+
+        write_varint(&mut payload, self.n as u64);
+        for v in 0..self.n{
+            let original = self.reverse_map[&v];
+            let data = &self.node_data[&original];
+            write_varint(&mut payload, original as u64);
+            write_string(&mut payload, &data.label);
+            write_string(&mut payload, &data.subject);
+            write_varint(&mut payload, data.features.len() as u64);
+            if data.features.iter().all(|feature| *feature <= 1){
+                payload.push(FEATURES_BITS);
+                for chunk in data.features.chunks(8){
+                    payload.push(chunk.iter().enumerate().fold(0, |byte, (bit, feature)| byte | (feature << bit)));
+                }
+            }
+            else{
+                payload.push(FEATURES_RAW);
+                payload.extend_from_slice(&data.features);
+            }
+        }
+        for neighbors in self.outedges.iter(){
+            write_varint(&mut payload, neighbors.len() as u64);
+            for w in neighbors.iter(){
+                write_varint(&mut payload, *w as u64);
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&fnv1a(FNV_OFFSET, &payload).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, bytes)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    //Read a cache written by save_binary, checking the magic, version and checksum
+    pub fn load_binary(path:impl AsRef<Path>) -> Result<Self,String>{
+        read_cache(path.as_ref()).map(|(graph, _)| graph)
+    }
+
+    //The cached graph if it exists, is intact and was built from exactly these unchanged sources, otherwise None
+    pub fn load_binary_if_fresh(path:impl AsRef<Path>, sources:&[&Path], validation:CacheValidation) -> Result<Option<Self>,String>{
+        let (graph, fingerprints) = match read_cache(path.as_ref()){
+            Ok(decoded) => decoded,
+            Err(_) => return Ok(None)
+        };
+        if fingerprints.len() != sources.len(){
+            return Ok(None);
+        }
+        for (fingerprint, source) in fingerprints.iter().zip(sources.iter()){
+            if !fingerprint.matches(source, validation)?{
+                return Ok(None);
+            }
+        }
+        Ok(Some(graph))
+    }
+
+    //from_csvs through a binary cache: reuse `cache_path` while both csv files are unchanged, otherwise parse
+    //them and rewrite the cache. A cache that can't be written only costs the speedup, so that is just reported.
+    pub fn from_csvs_cached(edge_path:impl AsRef<Path>, node_path:impl AsRef<Path>, cache_path:impl AsRef<Path>, validation:CacheValidation) -> Result<Self,String>{
+        let sources = [edge_path.as_ref(), node_path.as_ref()];
+        if let Some(graph) = Graph::load_binary_if_fresh(&cache_path, &sources, validation)?{
+            return Ok(graph);
+        }
+        //Fingerprint the sources before parsing them, so a file edited mid-parse leaves a cache that looks stale
+        let fingerprints = fingerprint_all(&sources, validation)?;
+        let graph = Graph::from_csvs(&edge_path, &node_path)?;
+        if let Err(e) = graph.write_cache(cache_path.as_ref(), &fingerprints){
+            eprintln!("Could not write graph cache {}: {}", cache_path.as_ref().display(), e);
+        }
+        Ok(graph)
+    }
+}
+
+fn fingerprint_all(sources:&[&Path], validation:CacheValidation) -> Result<Vec<SourceFingerprint>,String>{
+    sources.iter().map(|source| SourceFingerprint::of(source, validation == CacheValidation::ContentHash)).collect()
+}
+
+#[cfg(not(feature = "mmap"))]
+fn read_cache(path:&Path) -> Result<(Graph, Vec<SourceFingerprint>),String>{
+    let bytes = fs::read(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    decode(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+//Same as above through a memory map instead of copying the file into memory first
+#[cfg(feature = "mmap")]
+fn read_cache(path:&Path) -> Result<(Graph, Vec<SourceFingerprint>),String>{
+    let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    //Safety: the file must not be truncated or rewritten in place while it is mapped. Shrinking it makes reads of the
+    //lost pages raise SIGBUS instead of failing the length or checksum checks. save_binary only ever replaces
+    //the file by renaming a new one over it, which leaves this mapping intact; other writers have to do the same.
+    let map = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| format!("Could not map {}: {}", path.display(), e))?;
+    decode(&map).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn decode(bytes:&[u8]) -> Result<(Graph, Vec<SourceFingerprint>),String>{
+    if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC{
+        return Err(String::from("not a graph cache"));
+    }
+    let version = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+    if version != CACHE_VERSION{
+        return Err(format!("cache version {} but this build reads version {}", version, CACHE_VERSION));
+    }
+    let payload_len = u64::from_le_bytes(bytes[12..20].try_into().unwrap()) as usize;
+    let checksum = u64::from_le_bytes(bytes[20..28].try_into().unwrap());
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() != payload_len || fnv1a(FNV_OFFSET, payload) != checksum{
+        return Err(String::from("checksum mismatch, the cache is truncated or corrupt"));
+    }
+
+    let mut reader = ByteReader{bytes:payload, position:0};
+    let num_sources = reader.varint()?;
+    let mut fingerprints = Vec::new();
+    for _ in 0..num_sources{
+        fingerprints.push(SourceFingerprint{
+            path:reader.string()?,
+            modified_secs:reader.varint()?,
+            modified_nanos:reader.varint()? as u32,
+            size:reader.varint()?,
+            hash:u64::from_le_bytes(reader.take(8)?.try_into().unwrap())
+        });
+    }
+
+    //Counts come from the file, so reserve no more than the rest of the payload could possibly hold
+    let n = reader.varint()? as usize;
+    let mut node_data = HashMap::with_capacity(n.min(reader.remaining()));
+    let mut reverse_map = HashMap::with_capacity(n.min(reader.remaining()));
+    for mapped_node in 0..n{
+        let original = reader.varint()? as usize;
+        let label = reader.string()?;
+        let subject = reader.string()?;
+        let num_features = reader.varint()? as usize;
+        let features = match reader.byte()?{
+            FEATURES_BITS => reader.take(num_features.div_ceil(8))?.iter()
+                .flat_map(|byte| (0..8).map(move |bit| (byte >> bit) & 1))
+                .take(num_features)
+                .collect(),
+            FEATURES_RAW => reader.take(num_features)?.to_vec(),
+            tag => return Err(format!("unknown feature encoding {}", tag))
+        };
+        if node_data.contains_key(&original){
+            return Err(format!("node id {} appears twice", original));
+        }
+        node_data.insert(original, NodeData{mapped_node, label, subject, features});
+        reverse_map.insert(mapped_node, original);
+    }
+    let mut outedges: AdjacencyList = Vec::with_capacity(n.min(reader.remaining()));
+    for _ in 0..n{
+        let degree = reader.varint()? as usize;
+        let mut neighbors = Vec::with_capacity(degree.min(reader.remaining()));
+        for _ in 0..degree{
+            let w = reader.varint()? as usize;
+            if w >= n{
+                return Err(format!("edge to node {} of {}", w, n));
+            }
+            neighbors.push(w);
+        }
+        outedges.push(neighbors);
+    }
+    Ok((Graph{n, outedges, node_data, reverse_map}, fingerprints))
+}
+
+struct ByteReader<'a>{
+    bytes:&'a [u8],
+    position:usize
+}
+
+impl<'a> ByteReader<'a>{
+    fn remaining(&self) -> usize{
+        self.bytes.len() - self.position
+    }
+
+    fn take(&mut self, len:usize) -> Result<&'a [u8],String>{
+        let end = self.position.checked_add(len).filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| String::from("unexpected end of cache"))?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8,String>{
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64,String>{
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7){
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0{
+                return Ok(value);
+            }
+        }
+        Err(String::from("varint longer than 64 bits"))
+    }
+
+    fn string(&mut self) -> Result<String,String>{
+        let len = self.varint()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|e| e.to_string())
+    }
+}
+
+//LEB128: seven bits per byte, high bit set on every byte but the last
+fn write_varint(bytes:&mut Vec<u8>, mut value:u64){
+    while value >= 0x80{
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_string(bytes:&mut Vec<u8>, value:&str){
+    write_varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+fn fnv1a(mut hash:u64, bytes:&[u8]) -> u64{
+    for byte in bytes{
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn hash_file(path:&Path) -> Result<u64,String>{
+    let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let mut reader = BufReader::new(file);
+    let mut buffer = vec![0;1 << 16];
+    let mut hash = FNV_OFFSET;
+    loop{
+        let read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0{
+            return Ok(hash);
+        }
+        hash = fnv1a(hash, &buffer[..read]);
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use std::fs;
+use crate::graph::*;
+use crate::graph::export::json_string;
+use crate::graph::import::{DEFAULT_LABEL,DEFAULT_SUBJECT};
+
+//Examples kept per kind of problem in a DataQualityReport
+pub const MAX_EXAMPLES: usize = 5;
+
+//What to do with a citation whose citing or cited paper is not in the nodes file
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MissingEndpoints{
+    //Stop at the first one with an error
+    #[default]
+    Fail,
+    //Leave the citation out
+    Drop,
+    //Leave the citation out and list every one in DataQualityReport::dangling_edges
+    Collect,
+    //Add the missing paper with the default label and subject and no features
+    Create
+}
+
+//Which problems to fix while building a graph. The default keeps every citation and fails on missing papers,
+//which is what from_csvs does.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CleaningPolicy{
+    //Keep only the first copy of a repeated citation
+    pub dedupe:bool,
+    //Drop citations of a paper to itself
+    pub drop_self_loops:bool,
+    pub missing_endpoints:MissingEndpoints
+}
+
+//How often one kind of problem occurred, with the first few occurrences
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Issue<T>{
+    pub count:usize,
+    pub examples:Vec<T>
+}
+impl<T> Issue<T>{
+    pub(crate) fn record(&mut self, example:T){
+        self.count += 1;
+        if self.examples.len() < MAX_EXAMPLES{
+            self.examples.push(example);
+        }
+    }
+}
+impl<T> Default for Issue<T>{
+    fn default() -> Self{
+        Issue{count:0, examples:Vec::new()}
+    }
+}
+
+//Problems found in the input, by original ids. Problems are counted whether or not the policy fixed them.
+#[derive(Debug,Clone,PartialEq,Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataQualityReport{
+    pub policy:CleaningPolicy,
+    pub nodes_read:usize,
+    pub edges_read:usize,
+    //Citations left in the graph
+    pub edges_kept:usize,
+    //Node rows repeating an id, only the first row is used
+    pub duplicate_nodes:Issue<usize>,
+    //Copies of a citation after its first occurrence
+    pub duplicate_edges:Issue<(usize,usize)>,
+    pub self_loops:Issue<(usize,usize)>,
+    //Citations with a paper missing from the nodes file
+    pub missing_endpoints:Issue<(usize,usize)>,
+    //Distinct missing papers, sorted; with MissingEndpoints::Create these are the papers that were added
+    pub missing_nodes:Vec<usize>,
+    //Every citation with a missing paper, only filled in by MissingEndpoints::Collect
+    pub dangling_edges:Vec<(usize,usize)>
+}
+
+impl DataQualityReport{
+    pub fn is_clean(&self) -> bool{
+        self.duplicate_nodes.count == 0 && self.duplicate_edges.count == 0 && self.self_loops.count == 0 && self.missing_endpoints.count == 0
+    }
+
+    //Human readable summary, one problem per line
+    pub fn summary(&self) -> String{
+        let edge_examples = |issue:&Issue<(usize,usize)>| issue.examples.iter().map(|(v, w)| format!("{} -> {}", v, w)).collect::<Vec<_>>().join(", ");
+        let mut lines = vec![format!("Read {} papers and {} citations, kept {} citations", self.nodes_read, self.edges_read, self.edges_kept)];
+        if self.duplicate_nodes.count > 0{
+            let ids: Vec<String> = self.duplicate_nodes.examples.iter().map(|id| id.to_string()).collect();
+            lines.push(format!("{} repeated paper rows, e.g. {}", self.duplicate_nodes.count, ids.join(", ")));
+        }
+        if self.duplicate_edges.count > 0{
+            lines.push(format!("{} duplicate citations ({}), e.g. {}", self.duplicate_edges.count, if self.policy.dedupe { "dropped" } else { "kept" }, edge_examples(&self.duplicate_edges)));
+        }
+        if self.self_loops.count > 0{
+            lines.push(format!("{} self-citations ({}), e.g. {}", self.self_loops.count, if self.policy.drop_self_loops { "dropped" } else { "kept" }, edge_examples(&self.self_loops)));
+        }
+        if self.missing_endpoints.count > 0{
+            let action = if self.policy.missing_endpoints == MissingEndpoints::Create { "papers created" } else { "citations dropped" };
+            lines.push(format!("{} citations of {} papers missing from the nodes file ({}), e.g. {}",
+                self.missing_endpoints.count, self.missing_nodes.len(), action, edge_examples(&self.missing_endpoints)));
+        }
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self) -> String{
+        let edges = |edges:&[(usize,usize)]| edges.iter().map(|(v, w)| format!("[{}, {}]", v, w)).collect::<Vec<_>>().join(", ");
+        let ids = |ids:&[usize]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ");
+        let issue = |issue:&Issue<(usize,usize)>| format!("{{\"count\": {}, \"examples\": [{}]}}", issue.count, edges(&issue.examples));
+        let missing_endpoints = format!("{:?}", self.policy.missing_endpoints).to_lowercase();
+        format!(
+            "{{\n  \"policy\": {{\"dedupe\": {}, \"drop_self_loops\": {}, \"missing_endpoints\": {}}},\n  \"nodes_read\": {},\n  \"edges_read\": {},\n  \"edges_kept\": {},\n  \"duplicate_nodes\": {{\"count\": {}, \"examples\": [{}]}},\n  \"duplicate_edges\": {},\n  \"self_loops\": {},\n  \"missing_endpoints\": {},\n  \"missing_nodes\": [{}],\n  \"dangling_edges\": [{}]\n}}\n",
+            self.policy.dedupe, self.policy.drop_self_loops, json_string(&missing_endpoints),
+            self.nodes_read, self.edges_read, self.edges_kept,
+            self.duplicate_nodes.count, ids(&self.duplicate_nodes.examples),
+            issue(&self.duplicate_edges), issue(&self.self_loops), issue(&self.missing_endpoints),
+            ids(&self.missing_nodes), edges(&self.dangling_edges)
+        )
+    }
+
+    pub fn write_json(&self, output_file:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>>{
+        fs::write(output_file, self.to_json())?;
+        Ok(())
+    }
+}
+
+impl Graph{
+    //Read the input csv files, fixing problems as the policy says, and report everything that was found
+    pub fn from_csvs_cleaned(edge_path:impl AsRef<Path>, node_path:impl AsRef<Path>, policy:&CleaningPolicy) -> Result<(Self, DataQualityReport),String>{
+        let ((mut node_data, mut reverse_map), repeated) = Graph::read_node_csv(node_path.as_ref())?;
+        let edges = Graph::read_edge_csv(edge_path.as_ref())?;
+        let mut report = DataQualityReport{policy:*policy, nodes_read:node_data.len() + repeated.len(), ..DataQualityReport::default()};
+        for id in repeated{
+            report.duplicate_nodes.record(id);
+        }
+        let edges = clean_edges(&mut node_data, &mut reverse_map, &edges, &mut report)?;
+        Ok((Graph::create_directed(node_data.len(), &edges, node_data, reverse_map), report))
+    }
+
+    //Dedupe and drop self-citations in a graph that is already loaded, from any format. Every paper already
+    //exists, so the missing endpoint policy has nothing to do.
+    pub fn clean(&mut self, policy:&CleaningPolicy) -> DataQualityReport{
+        let edges: Vec<(usize,usize)> = self.outedges.iter().enumerate()
+            .flat_map(|(v, neighbors)| neighbors.iter().map(move |w| (v, *w)))
+            .map(|(v, w)| (self.reverse_map[&v], self.reverse_map[&w]))
+            .collect();
+        let mut report = DataQualityReport{policy:*policy, nodes_read:self.n, ..DataQualityReport::default()};
+        let edges = clean_edges(&mut self.node_data, &mut self.reverse_map, &edges, &mut report).unwrap();
+        self.outedges = vec![vec![];self.n];
+        for (v, w) in edges{
+            self.outedges[v].push(w);
+        }
+        report
+    }
+}
+
+//Map citations by original ids to mapped edges, applying the policy and filling in the edge counts of the report
+fn clean_edges(
+    node_data:&mut HashMap<usize,NodeData>,
+    reverse_map:&mut HashMap<usize,usize>,
+    edges:&[(usize,usize)],
+    report:&mut DataQualityReport
+) -> Result<Vec<Edge>,String>{
+    let mut missing_nodes = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut kept = Vec::with_capacity(edges.len());
+    for (citing, cited) in edges.iter().copied(){
+        let edge = match resolve_edge(node_data, reverse_map, citing, cited, &mut missing_nodes, report)?{
+            Some(edge) => edge,
+            None => continue
+        };
+        if !seen.insert(edge){
+            report.duplicate_edges.record((citing, cited));
+            if report.policy.dedupe{
+                continue;
+            }
+        }
+        kept.push(edge);
+    }
+    report.missing_nodes = missing_nodes.into_iter().collect();
+    report.missing_nodes.sort();
+    report.edges_read = edges.len();
+    report.edges_kept = kept.len();
+    Ok(kept)
+}
+
+//Apply the missing endpoint and self-loop parts of the policy to one citation, recording what was found.
+//Returns the mapped edge, or None when the citation is left out. Duplicates are up to the caller.
+pub(crate) fn resolve_edge(
+    node_data:&mut HashMap<usize,NodeData>,
+    reverse_map:&mut HashMap<usize,usize>,
+    citing:usize,
+    cited:usize,
+    missing_nodes:&mut HashSet<usize>,
+    report:&mut DataQualityReport
+) -> Result<Option<Edge>,String>{
+    let policy = report.policy;
+    let mut missing: Vec<usize> = [citing, cited].into_iter().filter(|id| !node_data.contains_key(id)).collect();
+    //A missing paper citing itself is still only one paper
+    missing.dedup();
+    if !missing.is_empty(){
+        if policy.missing_endpoints == MissingEndpoints::Fail{
+            return Err(if missing[0] == citing {
+                format!("Node ID {} not found in node data", citing)
+            } else {
+                format!("Target Node ID {} not found in node data", cited)
+            });
+        }
+        report.missing_endpoints.record((citing, cited));
+        missing_nodes.extend(missing.iter().copied());
+        match policy.missing_endpoints{
+            MissingEndpoints::Create => {
+                for id in missing{
+                    let mapped_node = node_data.len();
+                    node_data.insert(id, NodeData{mapped_node, label:String::from(DEFAULT_LABEL), subject:String::from(DEFAULT_SUBJECT), features:Vec::new()});
+                    reverse_map.insert(mapped_node, id);
+                }
+            },
+            MissingEndpoints::Collect => {
+                report.dangling_edges.push((citing, cited));
+                return Ok(None);
+            },
+            _ => return Ok(None)
+        }
+    }
+    if citing == cited{
+        report.self_loops.record((citing, cited));
+        if policy.drop_self_loops{
+            return Ok(None);
+        }
+    }
+    Ok(Some((node_data[&citing].mapped_node, node_data[&cited].mapped_node)))
+}
@@ -0,0 +1,68 @@
+use crate::graph::*;
+
+//Triangle and clustering statistics of the undirected projection
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClusteringStats{
+    //triangles[v] is the number of triangles through v
+    pub triangles:Vec<usize>,
+    //Distinct triangles in the graph
+    pub total_triangles:usize,
+    //3 * triangles / connected triples, 0 without any triples
+    pub transitivity:f64,
+    //Local clustering coefficient of each node, 0 for nodes with fewer than two neighbours
+    pub local_clustering:Vec<f64>,
+    //Mean of local_clustering over every node
+    pub average_clustering:f64
+}
+
+impl Graph{
+    //Triangles through every node of the undirected projection. Each edge is oriented from the endpoint with the
+    //lower (degree, id) rank to the higher one, so every node only scans its higher ranked neighbours and each
+    //triangle is found exactly once in O(m sqrt(m)).
+    pub fn triangle_counts(&self) -> Vec<usize>{
+        let adj_list = self.undirected_adjacency();
+        let rank = |v:Vertex| (adj_list[v].len(), v);
+        let forward: AdjacencyList = adj_list.iter().enumerate()
+            .map(|(v, neighbors)| neighbors.iter().copied().filter(|w| rank(*w) > rank(v)).collect())
+            .collect();
+        let mut triangles = vec![0;self.n];
+        let mut marked = vec![false;self.n];
+        for u in 0..self.n{
+            for v in forward[u].iter(){
+                marked[*v] = true;
+            }
+            for v in forward[u].iter(){
+                for w in forward[*v].iter(){
+                    if marked[*w]{
+                        triangles[u] += 1;
+                        triangles[*v] += 1;
+                        triangles[*w] += 1;
+                    }
+                }
+            }
+            for v in forward[u].iter(){
+                marked[*v] = false;
+            }
+        }
+        triangles
+    }
+
+    pub fn clustering(&self) -> ClusteringStats{
+        let triangles = self.triangle_counts();
+        let degrees: Vec<usize> = self.undirected_adjacency().iter().map(|neighbors| neighbors.len()).collect();
+        let triples = |d:usize| d * d.saturating_sub(1) / 2;
+        let local_clustering: Vec<f64> = triangles.iter().zip(degrees.iter())
+            .map(|(t, d)| if *d < 2 { 0.0 } else { *t as f64 / triples(*d) as f64 })
+            .collect();
+        let closed: usize = triangles.iter().sum();
+        let connected_triples: usize = degrees.iter().map(|d| triples(*d)).sum();
+        ClusteringStats{
+            total_triangles:closed / 3,
+            transitivity:if connected_triples == 0 { 0.0 } else { closed as f64 / connected_triples as f64 },
+            average_clustering:if self.n == 0 { 0.0 } else { local_clustering.iter().sum::<f64>() / self.n as f64 },
+            local_clustering,
+            triangles
+        }
+    }
+}
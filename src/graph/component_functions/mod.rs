@@ -1,14 +1,16 @@
 use crate::graph::*;
-pub fn mark_component_bfs(vertex:Vertex,graph:&Graph,component:&mut Vec<Option<Component>>, component_no:Component){
+//Label everything reachable from `vertex` in `adj_list`. Pass the undirected adjacency list so that a paper
+//only reached through an incoming citation still joins the component.
+pub(crate) fn mark_component_bfs(vertex:Vertex,adj_list:&[Vec<Vertex>],component:&mut [Option<Component>], component_no:Component){
     component[vertex] = Some(component_no);
 
     let mut queue = VecDeque::new();
     queue.push_back(vertex);
 
     while let Some(v) = queue.pop_front(){
-        for u in graph.outedges[v].iter(){
+        for u in adj_list[v].iter(){
             //If not visited
-            if let None = component[*u]{
+            if component[*u].is_none(){
                 component[*u] = Some(component_no);
                 queue.push_back(*u);
             }
@@ -16,14 +18,40 @@ pub fn mark_component_bfs(vertex:Vertex,graph:&Graph,component:&mut Vec<Option<C
     }
 }
 
-pub fn count_components(component:&Vec<Option<Component>>,num_components:usize) -> Vec<usize>{
+//Parallel label propagation with pointer jumping: every node repeatedly takes the smallest label among itself and
+//its neighbours, then the label of its label, until nothing changes. Labels always name a node of the same
+//component, so each node ends up labelled with the smallest node of its component.
+#[cfg(feature = "parallel")]
+pub(crate) fn propagate_min_labels(adj_list:&[Vec<Vertex>]) -> Vec<Vertex>{
+    use rayon::prelude::*;
+    let mut labels: Vec<Vertex> = (0..adj_list.len()).collect();
+    loop{
+        let hooked: Vec<Vertex> = adj_list.par_iter().enumerate()
+            .map(|(v, neighbors)| neighbors.iter().fold(labels[v], |label, w| label.min(labels[*w])))
+            .collect();
+        let mut jumped = hooked;
+        loop{
+            let next: Vec<Vertex> = jumped.par_iter().map(|label| jumped[*label]).collect();
+            if next == jumped{
+                break;
+            }
+            jumped = next;
+        }
+        if jumped == labels{
+            return labels;
+        }
+        labels = jumped;
+    }
+}
+
+pub fn count_components(component:&[Option<Component>],num_components:usize) -> Vec<usize>{
     //Get the count of nodes in each component
     let mut component_counts = vec![0;num_components];
     //Component counts from BFS are 1 indexed, but  unwrap() - 1 adjusts to a 0-index
     component.iter().for_each(|x| component_counts[x.unwrap() -1] += 1);
-    return component_counts;
+    component_counts
 }
-pub fn get_component_scale(component:&Vec<Option<Component>>, num_components:usize,sort:bool) -> Vec<f64>{
+pub fn get_component_scale(component:&[Option<Component>], num_components:usize,sort:bool) -> Vec<f64>{
     //Calculates in descending order the aggregate % of data encapsulated by the largest components 
     let mut component_counts = count_components(component, num_components);
     if sort{
@@ -31,12 +59,231 @@ pub fn get_component_scale(component:&Vec<Option<Component>>, num_components:usi
         component_counts.reverse();
     }
     let mut aggregate_component_counts = vec![0.0;num_components + 1];
-    let total = component_counts.iter().fold(0,|acc, x| acc + x) as f64;
+    let total = component_counts.iter().sum::<usize>() as f64;
     let mut running_sum = 0;
     for (index, elem) in component_counts.iter().enumerate(){
         running_sum += elem;
         let aggregate = running_sum as f64 / total;
         aggregate_component_counts[index + 1] = aggregate;
     }
-    return aggregate_component_counts
-}
\ No newline at end of file
+    aggregate_component_counts
+}
+//Renumber components so that 1 is the largest, 2 the second largest and so on (ties keep their BFS order)
+pub fn relabel_by_size(component:&[Option<Component>], num_components:usize) -> Vec<Option<Component>>{
+    let component_counts = count_components(component, num_components);
+    let mut order: Vec<usize> = (0..num_components).collect();
+    order.sort_by_key(|index| std::cmp::Reverse(component_counts[*index]));
+    let mut new_label = vec![0;num_components];
+    for (rank, index) in order.iter().enumerate(){
+        new_label[*index] = rank + 1;
+    }
+    component.iter().map(|c| c.map(|c| new_label[c - 1])).collect()
+}
+//Which component a paper ended up in, with components numbered by size as in relabel_by_size
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentAssignment{
+    //Original paper id
+    pub node:usize,
+    pub component:Component,
+    pub component_size:usize,
+    pub subject:String
+}
+
+//One assignment per labelled paper, ordered by component and then original id
+pub fn component_assignments(graph:&Graph, component:&[Option<Component>], num_components:usize) -> Vec<ComponentAssignment>{
+    let relabeled = relabel_by_size(component, num_components);
+    let sizes = count_components(&relabeled, num_components);
+    let mut assignments: Vec<ComponentAssignment> = (0..graph.n).filter_map(|v| {
+        let component = relabeled.get(v).copied().flatten()?;
+        let node = graph.reverse_map.get(&v).copied().unwrap_or(v);
+        Some(ComponentAssignment{
+            node,
+            component,
+            component_size:sizes[component - 1],
+            subject:graph.node_data.get(&node).map_or_else(String::new, |data| data.subject.clone())
+        })
+    }).collect();
+    assignments.sort_by_key(|assignment| (assignment.component, assignment.node));
+    assignments
+}
+
+//Components up to this size get exact eccentricities (a BFS from every member), larger ones a double sweep estimate
+pub const EXACT_ECCENTRICITY_LIMIT: usize = 500;
+
+//Profile of a single connected component
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentSummary{
+    //Component number as returned by connected_components
+    pub component:Component,
+    pub size:usize,
+    //Citations with both ends inside the component
+    pub edges:usize,
+    //edges / (size * (size - 1)), the share of possible directed citations present
+    pub density:f64,
+    //Longest and smallest eccentricity on the undirected view of the component
+    pub diameter:usize,
+    pub radius:usize,
+    //False when diameter and radius are double sweep estimates (a lower and an upper bound)
+    pub exact_distances:bool,
+    //Papers per subject, most common first
+    pub subject_counts:Vec<(String,usize)>,
+    pub dominant_subject:String,
+    //Shannon entropy of the subject distribution in bits (0 when every paper shares one subject)
+    pub subject_entropy:f64,
+    //Original id of the member with the most citations, and that citation count
+    pub most_cited:usize,
+    pub most_cited_count:usize
+}
+
+//Breadth first distances over an adjacency list, only stepping onto nodes that `allowed` accepts
+pub(crate) fn bfs_distances_within(adj_list:&AdjacencyList, source:Vertex, allowed:impl Fn(Vertex) -> bool) -> Vec<Option<usize>>{
+    let mut distance = vec![None;adj_list.len()];
+    distance[source] = Some(0);
+    let mut queue = VecDeque::new();
+    queue.push_back(source);
+    while let Some(v) = queue.pop_front(){
+        let next = distance[v].unwrap() + 1;
+        for u in adj_list[v].iter(){
+            if distance[*u].is_none() && allowed(*u){
+                distance[*u] = Some(next);
+                queue.push_back(*u);
+            }
+        }
+    }
+    distance
+}
+
+//Position of the entry furthest from the source in `distance`, and its distance
+fn farthest(distance:&[Option<usize>]) -> (Vertex, usize){
+    distance.iter().enumerate()
+        .filter_map(|(v, d)| d.map(|d| (v, d)))
+        .max_by_key(|(v, d)| (*d, std::cmp::Reverse(*v)))
+        .unwrap()
+}
+
+//Distances from members[source] to the other members of its component, indexed like `members`. `local[v]` is
+//the position of v in its own component's member list, so the buffers only need to be as long as the component.
+fn member_distances(
+    adj_list:&AdjacencyList,
+    members:&[Vertex],
+    local:&[usize],
+    inside:impl Fn(Vertex) -> bool,
+    source:usize,
+    distance:&mut [Option<usize>],
+    queue:&mut VecDeque<Vertex>
+){
+    distance.fill(None);
+    distance[source] = Some(0);
+    queue.clear();
+    queue.push_back(members[source]);
+    while let Some(v) = queue.pop_front(){
+        let next = distance[local[v]].unwrap() + 1;
+        for u in adj_list[v].iter(){
+            if inside(*u) && distance[local[*u]].is_none(){
+                distance[local[*u]] = Some(next);
+                queue.push_back(*u);
+            }
+        }
+    }
+}
+
+//(diameter, radius, exact) of the nodes in `members`, measured on the undirected adjacency list
+fn diameter_and_radius(adj_list:&AdjacencyList, members:&[Vertex], local:&[usize], component:&[Option<Component>], label:Component) -> (usize, usize, bool){
+    let inside = |v:Vertex| component[v] == Some(label);
+    let mut distance = vec![None;members.len()];
+    let mut queue = VecDeque::new();
+    if members.len() <= EXACT_ECCENTRICITY_LIMIT{
+        let eccentricities: Vec<usize> = (0..members.len())
+            .map(|source| {
+                member_distances(adj_list, members, local, inside, source, &mut distance, &mut queue);
+                farthest(&distance).1
+            })
+            .collect();
+        return (*eccentricities.iter().max().unwrap(), *eccentricities.iter().min().unwrap(), true);
+    }
+    //Double sweep: the node furthest from anywhere is an end of a long path, and the furthest node from it
+    //gives a diameter lower bound. The middle of that path is a good center, and its eccentricity bounds the radius.
+    member_distances(adj_list, members, local, inside, 0, &mut distance, &mut queue);
+    let (start, _) = farthest(&distance);
+    let mut from_start = vec![None;members.len()];
+    member_distances(adj_list, members, local, inside, start, &mut from_start, &mut queue);
+    let (end, diameter) = farthest(&from_start);
+    let mut from_end = vec![None;members.len()];
+    member_distances(adj_list, members, local, inside, end, &mut from_end, &mut queue);
+    let center = (0..members.len())
+        .min_by_key(|i| from_start[*i].unwrap().max(from_end[*i].unwrap()))
+        .unwrap();
+    member_distances(adj_list, members, local, inside, center, &mut distance, &mut queue);
+    let radius = farthest(&distance).1;
+    (diameter, radius, false)
+}
+
+pub fn summarize_components(graph:&Graph, component:&[Option<Component>], num_components:usize) -> Vec<ComponentSummary>{
+    let mut members: Vec<Vec<Vertex>> = vec![vec![];num_components];
+    for (v, c) in component.iter().enumerate(){
+        if let Some(c) = c{
+            members[c - 1].push(v);
+        }
+    }
+    let mut internal_edges = vec![0;num_components];
+    for (v, outedge) in graph.outedges.iter().enumerate(){
+        for w in outedge{
+            if let (Some(a), Some(b)) = (component[v], component[*w]){
+                if a == b{
+                    internal_edges[a - 1] += 1;
+                }
+            }
+        }
+    }
+    let in_degrees = graph.in_degrees();
+    let adj_list = graph.undirected_adjacency();
+    let mut local = vec![0;graph.n];
+    for nodes in members.iter(){
+        for (i, v) in nodes.iter().enumerate(){
+            local[*v] = i;
+        }
+    }
+
+    members.iter().enumerate().filter(|(_, nodes)| !nodes.is_empty()).map(|(index, nodes)| {
+        let label = index + 1;
+        let size = nodes.len();
+        let edges = internal_edges[index];
+        let density = if size < 2 { 0.0 } else { edges as f64 / (size * (size - 1)) as f64 };
+        let (diameter, radius, exact_distances) = diameter_and_radius(&adj_list, nodes, &local, component, label);
+
+        let mut subjects = HashMap::<String,usize>::new();
+        for v in nodes.iter(){
+            let subject = graph.reverse_map.get(v)
+                .and_then(|original| graph.node_data.get(original))
+                .map_or(String::new(), |data| data.subject.clone());
+            *subjects.entry(subject).or_insert(0) += 1;
+        }
+        let mut subject_counts: Vec<(String,usize)> = subjects.into_iter().collect();
+        subject_counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let subject_entropy = subject_counts.iter()
+            .map(|(_, count)| {
+                let p = *count as f64 / size as f64;
+                -p * p.log2()
+            })
+            .sum::<f64>()
+            .max(0.0);
+
+        let most_cited_node = *nodes.iter().max_by_key(|v| (in_degrees[**v], std::cmp::Reverse(**v))).unwrap();
+        ComponentSummary{
+            component:label,
+            size,
+            edges,
+            density,
+            diameter,
+            radius,
+            exact_distances,
+            dominant_subject:subject_counts[0].0.clone(),
+            subject_counts,
+            subject_entropy,
+            most_cited:graph.reverse_map.get(&most_cited_node).copied().unwrap_or(most_cited_node),
+            most_cited_count:in_degrees[most_cited_node]
+        }
+    }).collect()
+}
@@ -0,0 +1,118 @@
+use std::fs::File;
+use std::io::{BufRead,BufReader};
+use crate::graph::*;
+use crate::graph::union_find::DisjointSet;
+
+//Weak connectivity of a growing citation network, keyed by original paper ids. Papers and citations can be
+//added at any time and the components are kept up to date without a new BFS. Queries take &mut self
+//because finding a representative also shortens the paths behind it.
+#[derive(Debug,Clone,Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IncrementalConnectivity{
+    sets:DisjointSet,
+    //Original id -> element of the disjoint sets
+    index:HashMap<usize,usize>,
+    largest:usize
+}
+
+impl IncrementalConnectivity{
+    pub fn new() -> Self{
+        IncrementalConnectivity::default()
+    }
+
+    //Every paper and citation of the graph; the components match connected_components
+    pub fn from_graph(graph:&Graph) -> Self{
+        let mut connectivity = IncrementalConnectivity::new();
+        for v in 0..graph.n{
+            connectivity.add_node(graph.reverse_map[&v]);
+        }
+        for (v, neighbors) in graph.outedges.iter().enumerate(){
+            for w in neighbors.iter(){
+                connectivity.add_edge(graph.reverse_map[&v], graph.reverse_map[w]);
+            }
+        }
+        connectivity
+    }
+
+    //Add a paper as its own component, returning false if it was already known
+    pub fn add_node(&mut self, id:usize) -> bool{
+        if self.index.contains_key(&id){
+            return false;
+        }
+        let element = self.sets.push();
+        self.index.insert(id, element);
+        self.largest = self.largest.max(1);
+        true
+    }
+
+    //Add a citation, adding either paper if it is new. Returns true when it joined two components.
+    pub fn add_edge(&mut self, citing:usize, cited:usize) -> bool{
+        self.add_node(citing);
+        self.add_node(cited);
+        let (a, b) = (self.index[&citing], self.index[&cited]);
+        if !self.sets.union(a, b){
+            return false;
+        }
+        self.largest = self.largest.max(self.sets.set_size(a));
+        true
+    }
+
+    //Whether two papers are connected; unknown papers are never connected to anything
+    pub fn same_component(&mut self, a:usize, b:usize) -> bool{
+        match (self.index.get(&a).copied(), self.index.get(&b).copied()){
+            (Some(a), Some(b)) => self.sets.find(a) == self.sets.find(b),
+            _ => false
+        }
+    }
+
+    //Size of the component holding a paper, None if it is unknown
+    pub fn component_size(&mut self, id:usize) -> Option<usize>{
+        let element = *self.index.get(&id)?;
+        Some(self.sets.set_size(element))
+    }
+
+    //Sizes of every component, largest first
+    pub fn component_sizes(&self) -> Vec<usize>{
+        let mut sizes = self.sets.set_sizes();
+        sizes.sort_by_key(|size| std::cmp::Reverse(*size));
+        sizes
+    }
+
+    pub fn num_components(&self) -> usize{
+        self.sets.num_sets()
+    }
+
+    pub fn num_nodes(&self) -> usize{
+        self.index.len()
+    }
+
+    pub fn largest_component_size(&self) -> usize{
+        self.largest
+    }
+
+    //Fraction of the papers inside the largest component, like get_component_scale(..)[1]
+    pub fn largest_component_share(&self) -> f64{
+        if self.index.is_empty() { 0.0 } else { self.largest as f64 / self.index.len() as f64 }
+    }
+}
+
+//Lazily read "citing cited" pairs of original ids from a whitespace separated feed, skipping blank lines and # comments
+pub fn edge_feed(path:impl AsRef<Path>) -> Result<impl Iterator<Item=Result<(usize,usize),String>>,String>{
+    let path = path.as_ref().to_path_buf();
+    let file = File::open(&path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    Ok(BufReader::new(file).lines().enumerate().filter_map(move |(line_no, line)| {
+        let line = match line{
+            Ok(line) => line,
+            Err(e) => return Some(Err(e.to_string()))
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#'){
+            return None;
+        }
+        let ids: Vec<Option<usize>> = line.split_whitespace().take(2).map(|id| id.parse::<usize>().ok()).collect();
+        match ids.as_slice(){
+            [Some(citing), Some(cited)] => Some(Ok((*citing, *cited))),
+            _ => Some(Err(format!("Line {} of {} does not contain two numeric node ids", line_no + 1, path.display())))
+        }
+    }))
+}
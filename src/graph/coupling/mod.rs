@@ -0,0 +1,93 @@
+use crate::graph::*;
+
+//Weighted undirected graph derived from the citations. Every link is stored in both directions of
+//graph.outedges, so connected_components, the plots and the exporters work on it unchanged.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DerivedGraph{
+    pub graph:Graph,
+    //Laid out like graph.outedges: weights[v][i] belongs to the link v - outedges[v][i]
+    pub weights:Vec<Vec<usize>>
+}
+
+impl DerivedGraph{
+    //Every link once as (paper, paper, weight) with original ids, the smaller mapped node first
+    pub fn links(&self) -> Vec<(usize,usize,usize)>{
+        let mut links = Vec::new();
+        for (v, neighbors) in self.graph.outedges.iter().enumerate(){
+            for (w, weight) in neighbors.iter().zip(self.weights[v].iter()){
+                if v < *w{
+                    links.push((self.graph.reverse_map[&v], self.graph.reverse_map[w], *weight));
+                }
+            }
+        }
+        links
+    }
+
+    //source, target, weight csv of every link, heaviest first
+    pub fn write_links(&self, output_file:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>>{
+        let mut links = self.links();
+        links.sort_by_key(|(_, _, weight)| std::cmp::Reverse(*weight));
+        let mut writer = csv::Writer::from_path(output_file)?;
+        writer.write_record(["source", "target", "weight"])?;
+        for (v, w, weight) in links{
+            writer.write_record([v.to_string(), w.to_string(), weight.to_string()])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn num_links(&self) -> usize{
+        self.graph.calc_num_edges() / 2
+    }
+}
+
+impl Graph{
+    //Papers linked by the number of references they share. Links lighter than `min_weight` are dropped.
+    pub fn bibliographic_coupling(&self, min_weight:usize) -> DerivedGraph{
+        //Two papers share a reference whenever they both cite the same paper
+        let mut citers: AdjacencyList = vec![vec![];self.n];
+        for (v, neighbors) in self.outedges.iter().enumerate(){
+            for w in neighbors.iter(){
+                citers[*w].push(v);
+            }
+        }
+        self.derive_graph(&citers, min_weight)
+    }
+
+    //Papers linked by the number of papers citing both of them. Links lighter than `min_weight` are dropped.
+    pub fn co_citation(&self, min_weight:usize) -> DerivedGraph{
+        self.derive_graph(&self.outedges, min_weight)
+    }
+
+    //Link every pair of nodes that appear together in one of the groups, weighted by how many groups they share.
+    //A group of size d adds d(d-1)/2 pairs, so heavily cited hubs dominate the cost.
+    fn derive_graph(&self, groups:&AdjacencyList, min_weight:usize) -> DerivedGraph{
+        let mut pair_counts: HashMap<Edge,usize> = HashMap::new();
+        for group in groups.iter(){
+            let mut members = group.clone();
+            members.sort_unstable();
+            members.dedup();
+            for (i, v) in members.iter().enumerate(){
+                for w in members[i + 1..].iter(){
+                    *pair_counts.entry((*v, *w)).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut pairs: Vec<(Edge,usize)> = pair_counts.into_iter().filter(|(_, weight)| *weight >= min_weight).collect();
+        pairs.sort_unstable();
+
+        let mut outedges: AdjacencyList = vec![vec![];self.n];
+        let mut weights: Vec<Vec<usize>> = vec![vec![];self.n];
+        for ((v, w), weight) in pairs{
+            outedges[v].push(w);
+            weights[v].push(weight);
+            outedges[w].push(v);
+            weights[w].push(weight);
+        }
+        DerivedGraph{
+            graph:Graph{n:self.n, outedges, node_data:self.node_data.clone(), reverse_map:self.reverse_map.clone()},
+            weights
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::graph::*;
+
+//A paper whose removal splits its component, with original ids
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CutVertex{
+    pub node:usize,
+    //Sizes of the pieces its component falls into once it is removed, largest first
+    pub piece_sizes:Vec<usize>
+}
+impl CutVertex{
+    //Number of components its removal leaves where there was one
+    pub fn num_pieces(&self) -> usize{
+        self.piece_sizes.len()
+    }
+    //Papers cut off from the largest remaining piece
+    pub fn papers_separated(&self) -> usize{
+        self.piece_sizes.iter().skip(1).sum()
+    }
+}
+
+//Single points of failure of the undirected citation graph
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CutStructure{
+    //Sorted by papers_separated, most disruptive first
+    pub articulation_points:Vec<CutVertex>,
+    //Citations whose removal splits a component, as original ids in DFS order (parent, child)
+    pub bridges:Vec<(usize,usize)>
+}
+
+impl Graph{
+    //Tarjan's low-point DFS over the undirected projection, written with an explicit stack. A child whose subtree
+    //cannot reach above its parent is cut off when the parent goes, and is a bridge when it cannot reach the parent either.
+    pub fn cut_structure(&self) -> CutStructure{
+        let adj_list = self.undirected_adjacency();
+        let mut discovered: Vec<Option<usize>> = vec![None;self.n];
+        let mut low = vec![0;self.n];
+        let mut subtree_size = vec![1;self.n];
+        let mut parent: Vec<Option<Vertex>> = vec![None;self.n];
+        let mut pieces: Vec<Vec<usize>> = vec![vec![];self.n];
+        let mut next_index = 0;
+        let mut articulation_points = Vec::new();
+        let mut bridges = Vec::new();
+        for root in 0..self.n{
+            if discovered[root].is_some(){
+                continue;
+            }
+            let mut tree = vec![root];
+            discovered[root] = Some(next_index);
+            low[root] = next_index;
+            next_index += 1;
+            let mut frames = vec![(root, 0)];
+            while let Some(&mut (v, ref mut edge)) = frames.last_mut(){
+                if let Some(&w) = adj_list[v].get(*edge){
+                    *edge += 1;
+                    match discovered[w]{
+                        None => {
+                            discovered[w] = Some(next_index);
+                            low[w] = next_index;
+                            next_index += 1;
+                            parent[w] = Some(v);
+                            tree.push(w);
+                            frames.push((w, 0));
+                        },
+                        Some(w_index) if parent[v] != Some(w) => low[v] = low[v].min(w_index),
+                        _ => {}
+                    }
+                    continue;
+                }
+                frames.pop();
+                if let Some(p) = parent[v]{
+                    low[p] = low[p].min(low[v]);
+                    subtree_size[p] += subtree_size[v];
+                    let p_index = discovered[p].unwrap();
+                    if low[v] >= p_index{
+                        pieces[p].push(subtree_size[v]);
+                    }
+                    if low[v] > p_index{
+                        bridges.push((self.reverse_map[&p], self.reverse_map[&v]));
+                    }
+                }
+            }
+            //Whatever is not in a separated subtree stays connected through the parent's own ancestors
+            let component_size = subtree_size[root];
+            for v in tree{
+                let mut piece_sizes = std::mem::take(&mut pieces[v]);
+                let is_cut = if v == root { piece_sizes.len() >= 2 } else { !piece_sizes.is_empty() };
+                if !is_cut{
+                    continue;
+                }
+                let remaining = component_size - 1 - piece_sizes.iter().sum::<usize>();
+                if remaining > 0{
+                    piece_sizes.push(remaining);
+                }
+                piece_sizes.sort_by_key(|size| std::cmp::Reverse(*size));
+                articulation_points.push(CutVertex{node:self.reverse_map[&v], piece_sizes});
+            }
+        }
+        articulation_points.sort_by_key(|cut| std::cmp::Reverse(cut.papers_separated()));
+        CutStructure{articulation_points, bridges}
+    }
+}
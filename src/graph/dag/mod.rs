@@ -0,0 +1,293 @@
+use crate::graph::*;
+
+//A strongly connected component that contains at least one cycle, in original node ids
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CyclicComponent{
+    pub members:Vec<usize>,
+    //A shortest cycle through the first member, starting and ending at it
+    pub example_cycle:Vec<usize>
+}
+
+impl Graph{
+    //Kahn's algorithm; None when the citations contain a cycle
+    pub fn topological_sort(&self) -> Option<Vec<Vertex>>{
+        let mut in_degrees = self.in_degrees();
+        let mut queue: VecDeque<Vertex> = (0..self.n).filter(|v| in_degrees[*v] == 0).collect();
+        let mut order = Vec::with_capacity(self.n);
+        while let Some(v) = queue.pop_front(){
+            order.push(v);
+            for w in self.outedges[v].iter(){
+                in_degrees[*w] -= 1;
+                if in_degrees[*w] == 0{
+                    queue.push_back(*w);
+                }
+            }
+        }
+        if order.len() == self.n { Some(order) } else { None }
+    }
+
+    pub fn is_dag(&self) -> bool{
+        self.topological_sort().is_some()
+    }
+
+    //Tarjan's algorithm without recursion, so long citation chains can't overflow the stack.
+    //Components come out in reverse topological order of the condensation.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<Vertex>>{
+        let mut index: Vec<Option<usize>> = vec![None;self.n];
+        let mut lowlink = vec![0;self.n];
+        let mut on_stack = vec![false;self.n];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+        for root in 0..self.n{
+            if index[root].is_some(){
+                continue;
+            }
+            //Each frame is (node, position of the next out-edge to look at)
+            let mut frames = vec![(root, 0)];
+            index[root] = Some(next_index);
+            lowlink[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some(&mut (v, ref mut edge)) = frames.last_mut(){
+                if let Some(&w) = self.outedges[v].get(*edge){
+                    *edge += 1;
+                    match index[w]{
+                        None => {
+                            index[w] = Some(next_index);
+                            lowlink[w] = next_index;
+                            next_index += 1;
+                            stack.push(w);
+                            on_stack[w] = true;
+                            frames.push((w, 0));
+                        },
+                        Some(w_index) if on_stack[w] => lowlink[v] = lowlink[v].min(w_index),
+                        _ => {}
+                    }
+                    continue;
+                }
+                frames.pop();
+                if let Some(&(parent, _)) = frames.last(){
+                    lowlink[parent] = lowlink[parent].min(lowlink[v]);
+                }
+                if Some(lowlink[v]) == index[v]{
+                    let mut component = Vec::new();
+                    while let Some(w) = stack.pop(){
+                        on_stack[w] = false;
+                        component.push(w);
+                        if w == v{
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+
+    //Every strongly connected component with a cycle in it (more than one member, or a self-citation)
+    pub fn cyclic_components(&self) -> Vec<CyclicComponent>{
+        let mut result = Vec::new();
+        for mut members in self.strongly_connected_components(){
+            members.sort_unstable();
+            let start = members[0];
+            if members.len() == 1 && !self.outedges[start].contains(&start){
+                continue;
+            }
+            let cycle = self.shortest_cycle_through(start, &members);
+            result.push(CyclicComponent{
+                members:members.iter().map(|v| self.original_id(*v)).collect(),
+                example_cycle:cycle.iter().map(|v| self.original_id(*v)).collect()
+            });
+        }
+        result.sort_by_key(|component| std::cmp::Reverse(component.members.len()));
+        result
+    }
+
+    fn original_id(&self, node:Vertex) -> usize{
+        self.reverse_map.get(&node).copied().unwrap_or(node)
+    }
+
+    //BFS from `start` inside its strongly connected component until an edge leads back to it
+    fn shortest_cycle_through(&self, start:Vertex, members:&[Vertex]) -> Vec<Vertex>{
+        let mut parent: HashMap<Vertex,Vertex> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        while let Some(v) = queue.pop_front(){
+            for w in self.outedges[v].iter(){
+                if *w == start{
+                    let mut cycle = vec![start, v];
+                    while let Some(previous) = parent.get(cycle.last().unwrap()){
+                        cycle.push(*previous);
+                    }
+                    //cycle is now start <- v <- ... <- start, so turn it around
+                    cycle.reverse();
+                    return cycle;
+                }
+                if members.binary_search(w).is_ok() && !parent.contains_key(w) && *w != start{
+                    parent.insert(*w, v);
+                    queue.push_back(*w);
+                }
+            }
+        }
+        vec![start]
+    }
+
+    //Copy of the graph with a small feedback arc set removed so that no cycles remain, plus the removed
+    //citations as (citing, cited) original ids. Each cyclic component is ordered with the Eades-Lin-Smyth
+    //heuristic and edges pointing backwards are dropped. Any dropped edge that can be put back without
+    //closing a cycle is then restored, so the removed set is minimal (though not necessarily minimum).
+    //The ordering is linear in the edges, but restoring runs one search of the edge's strongly connected
+    //component per dropped edge, so it is O(F * (V + E)) for F dropped edges and quadratic in the worst case.
+    pub fn make_acyclic(&self) -> (Graph, Vec<(usize,usize)>){
+        let mut position = vec![0;self.n];
+        let mut component_of = vec![usize::MAX;self.n];
+        for (label, members) in self.strongly_connected_components().iter().enumerate(){
+            for v in members.iter(){
+                component_of[*v] = label;
+            }
+            if members.len() > 1{
+                for (rank, v) in self.eades_lin_smyth(members).iter().enumerate(){
+                    position[*v] = rank;
+                }
+            }
+        }
+
+        let mut outedges: AdjacencyList = vec![vec![];self.n];
+        let mut removed = Vec::new();
+        for (v, neighbors) in self.outedges.iter().enumerate(){
+            for w in neighbors.iter(){
+                let backwards = component_of[v] == component_of[*w] && position[v] >= position[*w];
+                if backwards { removed.push((v, *w)) } else { outedges[v].push(*w) }
+            }
+        }
+
+        //A path from w back to v stays inside their strongly connected component, so the search is limited to it.
+        //visited holds the stamp of the last search that reached each node, so the buffer is never cleared.
+        let mut visited = vec![0;self.n];
+        let mut queue = VecDeque::new();
+        let mut kept_removed = Vec::new();
+        for (stamp, (v, w)) in removed.into_iter().enumerate(){
+            //Safe to restore when w cannot already reach v
+            let closes_cycle = v == w || reaches_within(&outedges, &component_of, w, v, &mut visited, stamp + 1, &mut queue);
+            if closes_cycle { kept_removed.push((v, w)) } else { outedges[v].push(w) }
+        }
+
+        let graph = Graph{n:self.n, outedges, node_data:self.node_data.clone(), reverse_map:self.reverse_map.clone()};
+        let removed = kept_removed.iter().map(|(v, w)| (self.original_id(*v), self.original_id(*w))).collect();
+        (graph, removed)
+    }
+
+    //Eades-Lin-Smyth ordering of `members`: peel off sinks to the back and sources to the front, otherwise move the
+    //node with the largest out-degree minus in-degree to the front. Edges that point backwards form the arc set.
+    //Sinks, sources and every out-degree minus in-degree value get their own stack, as in the paper, so the whole
+    //ordering takes time linear in the edges of the component. Stacks are filled lazily: a node is pushed again
+    //whenever its degrees change, and entries that no longer describe it are skipped when popped.
+    fn eades_lin_smyth(&self, members:&[Vertex]) -> Vec<Vertex>{
+        let inside: HashMap<Vertex,usize> = members.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+        let k = members.len();
+        let mut out_neighbors: Vec<Vec<usize>> = vec![vec![];k];
+        let mut in_neighbors: Vec<Vec<usize>> = vec![vec![];k];
+        for (i, v) in members.iter().enumerate(){
+            for w in self.outedges[*v].iter(){
+                if let Some(&j) = inside.get(w){
+                    if i != j{
+                        out_neighbors[i].push(j);
+                        in_neighbors[j].push(i);
+                    }
+                }
+            }
+        }
+        let mut out_degree: Vec<usize> = out_neighbors.iter().map(|n| n.len()).collect();
+        let mut in_degree: Vec<usize> = in_neighbors.iter().map(|n| n.len()).collect();
+        //Out-degree minus in-degree shifted by the number of edges so it can index the buckets
+        let offset = in_degree.iter().sum::<usize>();
+        let delta = |i:usize, out_degree:&[usize], in_degree:&[usize]| offset + out_degree[i] - in_degree[i];
+        let mut buckets: Vec<Vec<usize>> = vec![vec![];2 * offset + 1];
+        let mut sinks = Vec::new();
+        let mut sources = Vec::new();
+        //Pushed in reverse so the lowest index comes off each stack first
+        for i in (0..k).rev(){
+            if out_degree[i] == 0 { sinks.push(i) }
+            if in_degree[i] == 0 { sources.push(i) }
+            buckets[delta(i, &out_degree, &in_degree)].push(i);
+        }
+        let mut max_bucket = buckets.len() - 1;
+        let mut removed = vec![false;k];
+        let mut front = Vec::with_capacity(k);
+        let mut back = Vec::new();
+
+        for _ in 0..k{
+            let mut sink = None;
+            while let Some(i) = sinks.pop(){
+                if !removed[i] { sink = Some(i); break; }
+            }
+            let i = if let Some(i) = sink{
+                back.push(members[i]);
+                i
+            }
+            else{
+                let mut source = None;
+                while let Some(i) = sources.pop(){
+                    if !removed[i] { source = Some(i); break; }
+                }
+                let i = source.unwrap_or_else(|| loop{
+                    match buckets[max_bucket].pop(){
+                        Some(i) if !removed[i] && delta(i, &out_degree, &in_degree) == max_bucket => break i,
+                        Some(_) => {},
+                        None => max_bucket -= 1
+                    }
+                });
+                front.push(members[i]);
+                i
+            };
+            removed[i] = true;
+            for j in out_neighbors[i].iter().copied().filter(|j| !removed[*j]){
+                in_degree[j] -= 1;
+                if in_degree[j] == 0 { sources.push(j) }
+                let bucket = delta(j, &out_degree, &in_degree);
+                buckets[bucket].push(j);
+                max_bucket = max_bucket.max(bucket);
+            }
+            for j in in_neighbors[i].iter().copied().filter(|j| !removed[*j]){
+                out_degree[j] -= 1;
+                if out_degree[j] == 0 { sinks.push(j) }
+                buckets[delta(j, &out_degree, &in_degree)].push(j);
+            }
+        }
+        back.reverse();
+        front.extend(back);
+        front
+    }
+}
+
+//Whether `target` can be reached from `source` without leaving the strongly connected component of `source`.
+//A node counts as visited once `visited` holds this search's `stamp`.
+fn reaches_within(
+    adj_list:&AdjacencyList,
+    component_of:&[usize],
+    source:Vertex,
+    target:Vertex,
+    visited:&mut [usize],
+    stamp:usize,
+    queue:&mut VecDeque<Vertex>
+) -> bool{
+    queue.clear();
+    visited[source] = stamp;
+    queue.push_back(source);
+    while let Some(v) = queue.pop_front(){
+        if v == target{
+            return true;
+        }
+        for w in adj_list[v].iter(){
+            if visited[*w] != stamp && component_of[*w] == component_of[source]{
+                visited[*w] = stamp;
+                queue.push_back(*w);
+            }
+        }
+    }
+    false
+}
@@ -0,0 +1,379 @@
+use std::fs::{self,File};
+use std::io::{BufWriter,Write};
+use std::path::Path;
+use crate::graph::*;
+use crate::graph::visualization_support::get_color_from_gradient;
+use crate::graph::component_functions::component_assignments;
+
+//Escape a string so it can be embedded as a JSON string literal (quotes included)
+pub(crate) fn json_string(value:&str) -> String{
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars(){
+        match c{
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            //Keeps "</script>" inside a string from closing the inlined script tag
+            '<' => escaped.push_str("\\u003c"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c)
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+//Original node id and metadata for a mapped node, falling back to the mapped index when the maps are empty
+pub(crate) fn node_metadata(graph:&Graph, node:Vertex) -> (usize, Option<&NodeData>){
+    let original = graph.reverse_map.get(&node).copied().unwrap_or(node);
+    (original, graph.node_data.get(&original))
+}
+
+//Graph, layout and coloring as the JSON object read by the page script
+fn html_graph_json(graph:&Graph, components:&[Option<Component>], num_components:usize, positions:&[Option<Point>]) -> String{
+    let mut subjects: Vec<String> = graph.node_data.values().map(|data| data.subject.clone()).collect();
+    subjects.sort();
+    subjects.dedup();
+
+    let mut nodes = Vec::with_capacity(graph.n);
+    for node in 0..graph.n{
+        let (x, y) = match positions.get(node).copied().flatten(){
+            Some(position) => position,
+            None => continue
+        };
+        let (original, data) = node_metadata(graph, node);
+        let (label, subject) = match data{
+            Some(data) => (data.label.as_str(), data.subject.as_str()),
+            None => ("", "")
+        };
+        let subject_index = subjects.iter().position(|s| s == subject).map_or(-1, |i| i as i64);
+        let (component, color) = match components.get(node).copied().flatten(){
+            Some(component) => {
+                let RGBAColor(r, g, b, _) = get_color_from_gradient(component, num_components);
+                (component as i64, format!("#{:02x}{:02x}{:02x}", r, g, b))
+            },
+            None => (-1, String::from("#888888"))
+        };
+        nodes.push(format!(
+            "{{\"i\":{},\"id\":{},\"label\":{},\"subject\":{},\"s\":{},\"component\":{},\"color\":{},\"x\":{},\"y\":{}}}",
+            node, original, json_string(label), json_string(subject), subject_index, component, json_string(&color), x, y
+        ));
+    }
+
+    let mut edges = Vec::with_capacity(graph.calc_num_edges());
+    for (node, neighbors) in graph.outedges.iter().enumerate(){
+        for neighbor in neighbors{
+            edges.push(format!("[{},{}]", node, neighbor));
+        }
+    }
+    let subjects: Vec<String> = subjects.iter().map(|s| json_string(s)).collect();
+    format!("{{\"nodes\":[{}],\"edges\":[{}],\"subjects\":[{}]}}", nodes.join(","), edges.join(","), subjects.join(","))
+}
+
+//Write a self-contained interactive page (no external scripts) with the layout, component/subject coloring and tooltips
+pub fn write_html(
+    graph:&Graph,
+    components:&[Option<Component>],
+    num_components:usize,
+    positions:&[Option<Point>],
+    output_file:impl AsRef<Path>,
+    title:&str
+) -> Result<(), Box<dyn std::error::Error>>{
+    let data = html_graph_json(graph, components, num_components, positions);
+    //Split at the data placeholder so neither the title nor the labels in the data are substituted again
+    let (head, tail) = HTML_TEMPLATE.split_once("__GRAPH_DATA__").ok_or("HTML template has no data placeholder")?;
+    let title = html_escape(title);
+    let page = format!("{}{}{}", head.replace("__TITLE_TEXT__", &title), data, tail.replace("__TITLE_TEXT__", &title));
+    fs::write(output_file, page)?;
+    Ok(())
+}
+
+fn html_escape(value:&str) -> String{
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+//One "node_id,component_id,component_size,subject" row per node, keyed by original node id.
+//Components are renumbered so that component 1 is the largest.
+pub fn write_component_assignments(
+    graph:&Graph,
+    component:&[Option<Component>],
+    num_components:usize,
+    output_file:impl AsRef<Path>
+) -> Result<(), Box<dyn std::error::Error>>{
+    let mut writer = csv::Writer::from_path(output_file)?;
+    writer.write_record(["node_id", "component_id", "component_size", "subject"])?;
+    for assignment in component_assignments(graph, component, num_components){
+        writer.write_record([assignment.node.to_string(), assignment.component.to_string(), assignment.component_size.to_string(), assignment.subject])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+//XML attribute and text escaping share the same rules as HTML for our purposes
+fn xml_escape(value:&str) -> String{
+    html_escape(value).replace('\'', "&apos;")
+}
+
+fn dot_string(value:&str) -> String{
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+//Features in the same bracketed form used by the nodes csv
+pub(crate) fn features_string(features:&[u8]) -> String{
+    let values: Vec<String> = features.iter().map(|f| f.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
+
+//Which computed values are written alongside the graph by the GraphML, GEXF and DOT writers
+#[derive(Debug,Clone)]
+pub struct ExportOptions<'a>{
+    //Write the one-hot word features of every node
+    pub include_features:bool,
+    //Component of each mapped node, as returned by connected_components
+    pub components:Option<&'a [Option<Component>]>,
+    //Named per-node scores indexed by mapped node, e.g. ("degree_centrality", &scores)
+    pub node_metrics:Vec<(&'a str, &'a [f64])>,
+    //Relationship written on every edge
+    pub edge_type:&'a str
+}
+impl Default for ExportOptions<'_>{
+    fn default() -> Self{
+        ExportOptions{
+            include_features:false,
+            components:None,
+            node_metrics:Vec::new(),
+            edge_type:"CITES"
+        }
+    }
+}
+
+//Every attribute of one node as (name, value) pairs, in the order the writers declare them
+fn node_attributes(graph:&Graph, node:Vertex, options:&ExportOptions) -> Vec<(String, String)>{
+    let (_, data) = node_metadata(graph, node);
+    let mut attributes = vec![
+        (String::from("label"), data.map_or(String::new(), |d| d.label.clone())),
+        (String::from("subject"), data.map_or(String::new(), |d| d.subject.clone()))
+    ];
+    if options.include_features{
+        attributes.push((String::from("features"), features_string(data.map_or(&[], |d| &d.features))));
+    }
+    if let Some(components) = options.components{
+        let component = components.get(node).copied().flatten().map_or(String::new(), |c| c.to_string());
+        attributes.push((String::from("component"), component));
+    }
+    for (name, values) in options.node_metrics.iter(){
+        attributes.push((name.to_string(), values.get(node).map_or(String::new(), |v| v.to_string())));
+    }
+    attributes
+}
+
+//Attribute names and their GraphML/GEXF types, matching node_attributes
+fn attribute_schema(options:&ExportOptions) -> Vec<(String, &'static str)>{
+    let mut schema = vec![(String::from("label"), "string"), (String::from("subject"), "string")];
+    if options.include_features{
+        schema.push((String::from("features"), "string"));
+    }
+    if options.components.is_some(){
+        schema.push((String::from("component"), "int"));
+    }
+    for (name, _) in options.node_metrics.iter(){
+        schema.push((name.to_string(), "double"));
+    }
+    schema
+}
+
+pub fn write_graphml(graph:&Graph, options:&ExportOptions, output_file:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>>{
+    let mut out = BufWriter::new(File::create(output_file)?);
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+    let schema = attribute_schema(options);
+    for (index, (name, kind)) in schema.iter().enumerate(){
+        writeln!(out, "  <key id=\"d{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>", index, xml_escape(name), kind)?;
+    }
+    writeln!(out, "  <key id=\"type\" for=\"edge\" attr.name=\"type\" attr.type=\"string\"/>")?;
+    writeln!(out, "  <graph id=\"G\" edgedefault=\"directed\">")?;
+    for node in 0..graph.n{
+        let (original, _) = node_metadata(graph, node);
+        writeln!(out, "    <node id=\"{}\">", original)?;
+        for (index, (_, value)) in node_attributes(graph, node, options).iter().enumerate(){
+            if !value.is_empty(){
+                writeln!(out, "      <data key=\"d{}\">{}</data>", index, xml_escape(value))?;
+            }
+        }
+        writeln!(out, "    </node>")?;
+    }
+    for (node, neighbors) in graph.outedges.iter().enumerate(){
+        let (source, _) = node_metadata(graph, node);
+        for neighbor in neighbors{
+            let (target, _) = node_metadata(graph, *neighbor);
+            writeln!(out, "    <edge source=\"{}\" target=\"{}\"><data key=\"type\">{}</data></edge>", source, target, xml_escape(options.edge_type))?;
+        }
+    }
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")?;
+    out.flush()?;
+    Ok(())
+}
+
+pub fn write_gexf(graph:&Graph, options:&ExportOptions, output_file:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>>{
+    let mut out = BufWriter::new(File::create(output_file)?);
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">")?;
+    writeln!(out, "  <graph defaultedgetype=\"directed\" mode=\"static\">")?;
+    //GEXF has its own label field, so the label is written there rather than as an attribute
+    let schema = attribute_schema(options);
+    writeln!(out, "    <attributes class=\"node\">")?;
+    for (index, (name, kind)) in schema.iter().enumerate().skip(1){
+        let kind = if *kind == "int" { "integer" } else { kind };
+        writeln!(out, "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>", index, xml_escape(name), kind)?;
+    }
+    writeln!(out, "    </attributes>")?;
+    writeln!(out, "    <nodes>")?;
+    for node in 0..graph.n{
+        let (original, _) = node_metadata(graph, node);
+        let attributes = node_attributes(graph, node, options);
+        writeln!(out, "      <node id=\"{}\" label=\"{}\">", original, xml_escape(&attributes[0].1))?;
+        writeln!(out, "        <attvalues>")?;
+        for (index, (_, value)) in attributes.iter().enumerate().skip(1){
+            if !value.is_empty(){
+                writeln!(out, "          <attvalue for=\"{}\" value=\"{}\"/>", index, xml_escape(value))?;
+            }
+        }
+        writeln!(out, "        </attvalues>")?;
+        writeln!(out, "      </node>")?;
+    }
+    writeln!(out, "    </nodes>")?;
+    writeln!(out, "    <edges>")?;
+    let mut edge_id = 0;
+    for (node, neighbors) in graph.outedges.iter().enumerate(){
+        let (source, _) = node_metadata(graph, node);
+        for neighbor in neighbors{
+            let (target, _) = node_metadata(graph, *neighbor);
+            writeln!(out, "      <edge id=\"{}\" source=\"{}\" target=\"{}\" label=\"{}\"/>", edge_id, source, target, xml_escape(options.edge_type))?;
+            edge_id += 1;
+        }
+    }
+    writeln!(out, "    </edges>")?;
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</gexf>")?;
+    out.flush()?;
+    Ok(())
+}
+
+pub fn write_dot(graph:&Graph, options:&ExportOptions, output_file:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>>{
+    let mut out = BufWriter::new(File::create(output_file)?);
+    writeln!(out, "digraph citations {{")?;
+    for node in 0..graph.n{
+        let (original, _) = node_metadata(graph, node);
+        let attributes: Vec<String> = node_attributes(graph, node, options).iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| format!("{}={}", dot_string(name), dot_string(value)))
+            .collect();
+        writeln!(out, "  {} [{}];", original, attributes.join(", "))?;
+    }
+    for (node, neighbors) in graph.outedges.iter().enumerate(){
+        let (source, _) = node_metadata(graph, node);
+        for neighbor in neighbors{
+            let (target, _) = node_metadata(graph, *neighbor);
+            writeln!(out, "  {} -> {} [type={}];", source, target, dot_string(options.edge_type))?;
+        }
+    }
+    writeln!(out, "}}")?;
+    out.flush()?;
+    Ok(())
+}
+
+const HTML_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>__TITLE_TEXT__</title>
+<style>
+  body { margin: 0; font-family: helvetica, sans-serif; background: #f5f5f5; }
+  header { padding: 10px 16px; display: flex; gap: 16px; align-items: center; }
+  h1 { font-size: 20px; margin: 0; }
+  #stats { color: #757575; }
+  canvas { display: block; background: #f5f5f5; }
+  #tooltip { position: absolute; pointer-events: none; background: #fff; border: 1px solid #bdbdbd;
+             padding: 6px 8px; font-size: 13px; display: none; box-shadow: 0 1px 4px rgba(0,0,0,0.2); }
+</style>
+</head>
+<body>
+<header>
+  <h1>__TITLE_TEXT__</h1>
+  <span id="stats"></span>
+  <label>Color by <select id="colorBy"><option value="component">component</option><option value="subject">subject</option></select></label>
+</header>
+<canvas id="graph"></canvas>
+<div id="tooltip"></div>
+<script>
+const graph = __GRAPH_DATA__;
+const palette = ["#1f77b4","#ff7f0e","#2ca02c","#d62728","#9467bd","#8c564b","#e377c2","#7f7f7f","#bcbd22","#17becf"];
+const canvas = document.getElementById("graph");
+const ctx = canvas.getContext("2d");
+const tooltip = document.getElementById("tooltip");
+const colorBy = document.getElementById("colorBy");
+const byIndex = new Map(graph.nodes.map(n => [n.i, n]));
+document.getElementById("stats").textContent = "V = " + graph.nodes.length + ", E = " + graph.edges.length;
+
+function nodeColor(n) {
+  if (colorBy.value === "subject") return n.s < 0 ? "#888888" : palette[n.s % palette.length];
+  return n.color;
+}
+function project(n) {
+  const size = Math.min(canvas.width, canvas.height);
+  const offsetX = (canvas.width - size) / 2, offsetY = (canvas.height - size) / 2;
+  return [offsetX + (n.x + 500) / 1000 * size, offsetY + (500 - n.y) / 1000 * size];
+}
+function draw() {
+  canvas.width = window.innerWidth;
+  canvas.height = window.innerHeight - canvas.offsetTop;
+  ctx.clearRect(0, 0, canvas.width, canvas.height);
+  ctx.strokeStyle = "rgba(0,151,167,0.35)";
+  ctx.beginPath();
+  for (const [s, t] of graph.edges) {
+    const a = byIndex.get(s), b = byIndex.get(t);
+    if (!a || !b) continue;
+    const [x1, y1] = project(a), [x2, y2] = project(b);
+    ctx.moveTo(x1, y1);
+    ctx.lineTo(x2, y2);
+  }
+  ctx.stroke();
+  for (const n of graph.nodes) {
+    const [x, y] = project(n);
+    ctx.fillStyle = nodeColor(n);
+    ctx.beginPath();
+    ctx.arc(x, y, 4, 0, 2 * Math.PI);
+    ctx.fill();
+  }
+}
+function escapeText(value) {
+  const div = document.createElement("div");
+  div.textContent = value;
+  return div.innerHTML;
+}
+canvas.addEventListener("mousemove", event => {
+  let best = null, bestDistance = 64;
+  for (const n of graph.nodes) {
+    const [x, y] = project(n);
+    const d = (x - event.offsetX) ** 2 + (y - event.offsetY) ** 2;
+    if (d < bestDistance) { best = n; bestDistance = d; }
+  }
+  if (!best) { tooltip.style.display = "none"; return; }
+  tooltip.innerHTML = "<b>Node " + best.id + "</b><br>Label: " + escapeText(best.label) +
+    "<br>Subject: " + escapeText(best.subject) + "<br>Component: " + best.component;
+  tooltip.style.left = (event.pageX + 12) + "px";
+  tooltip.style.top = (event.pageY + 12) + "px";
+  tooltip.style.display = "block";
+});
+canvas.addEventListener("mouseleave", () => { tooltip.style.display = "none"; });
+colorBy.addEventListener("change", draw);
+window.addEventListener("resize", draw);
+draw();
+</script>
+</body>
+</html>
+"##;
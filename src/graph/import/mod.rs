@@ -0,0 +1,318 @@
+use std::fs::{self,File};
+use std::io::{BufRead,BufReader};
+use std::path::Path;
+use quick_xml::events::{BytesStart,Event};
+use quick_xml::reader::Reader;
+use std::collections::HashSet;
+use crate::graph::*;
+
+//Label and subject given to nodes whose file carries no metadata for them
+pub const DEFAULT_LABEL: &str = "Paper";
+pub const DEFAULT_SUBJECT: &str = "Unknown";
+//Every row and column of a Matrix Market file becomes a paper up front, so larger size lines are refused
+//before anything is allocated for them
+const MAX_MATRIX_MARKET_NODES: usize = 1 << 26;
+
+//A node as read from a file, before ids are resolved and missing metadata is filled in
+#[derive(Debug,Clone,Default)]
+pub(crate) struct RawNode{
+    pub(crate) id:String,
+    pub(crate) label:Option<String>,
+    pub(crate) subject:Option<String>,
+    pub(crate) features:Option<Vec<u8>>
+}
+impl RawNode{
+    fn new(id:String) -> Self{
+        RawNode{id,..RawNode::default()}
+    }
+    //Store a named attribute if it is one NodeData knows about
+    fn set_attribute(&mut self, name:&str, value:String){
+        match name.to_lowercase().as_str(){
+            "label" | "labels" => self.label = Some(value),
+            "subject" => self.subject = Some(value),
+            "features" => self.features = Some(NodeData::parse_features(&value)),
+            _ => {}
+        }
+    }
+}
+
+//Map the raw ids to 0..n and build the graph, creating nodes for edge endpoints that were never declared.
+//Ids are kept as the original node ids when every one is numeric, otherwise nodes are numbered in file order
+//and the text id is used as the label when the file has none.
+pub(crate) fn build_graph(mut nodes:Vec<RawNode>, edges:&[(String,String)]) -> Graph{
+    //Repeated declarations of the same id keep the first one
+    let mut declared = HashSet::<String>::new();
+    nodes.retain(|node| declared.insert(node.id.clone()));
+    for (source, target) in edges.iter(){
+        for id in [source, target]{
+            if declared.insert(id.clone()){
+                nodes.push(RawNode::new(id.clone()));
+            }
+        }
+    }
+    //"7" and "007" would collide once parsed, so those files are numbered in order as well
+    let parsed: HashSet<usize> = nodes.iter().filter_map(|node| node.id.parse::<usize>().ok()).collect();
+    let numeric_ids = parsed.len() == nodes.len();
+
+    let n = nodes.len();
+    let mut node_data = HashMap::<usize,NodeData>::new();
+    let mut reverse_map = HashMap::<usize,usize>::new();
+    let mut mapped_of = HashMap::<String,usize>::new();
+    for (mapped_node, node) in nodes.into_iter().enumerate(){
+        let original = if numeric_ids { node.id.parse::<usize>().unwrap() } else { mapped_node };
+        let fallback_label = if numeric_ids { String::from(DEFAULT_LABEL) } else { node.id.clone() };
+        node_data.insert(original, NodeData{
+            mapped_node,
+            label:node.label.unwrap_or(fallback_label),
+            subject:node.subject.unwrap_or_else(|| String::from(DEFAULT_SUBJECT)),
+            features:node.features.unwrap_or_default()
+        });
+        reverse_map.insert(mapped_node, original);
+        mapped_of.insert(node.id, mapped_node);
+    }
+
+    let mapped_edges: Vec<Edge> = edges.iter().map(|(source, target)| (mapped_of[source], mapped_of[target])).collect();
+    Graph::create_directed(n, &mapped_edges, node_data, reverse_map)
+}
+
+fn attribute(element:&BytesStart, name:&[u8]) -> Result<Option<String>,String>{
+    for attr in element.attributes(){
+        let attr = attr.map_err(|e| e.to_string())?;
+        if attr.key.local_name().as_ref() == name{
+            return Ok(Some(attr.unescape_value().map_err(|e| e.to_string())?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn required_attribute(element:&BytesStart, name:&[u8]) -> Result<String,String>{
+    attribute(element, name)?.ok_or_else(|| format!(
+        "<{}> element is missing its {} attribute",
+        String::from_utf8_lossy(element.local_name().as_ref()),
+        String::from_utf8_lossy(name)
+    ))
+}
+
+fn xml_reader(path:&Path) -> Result<Reader<BufReader<File>>,String>{
+    let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    reader.config_mut().trim_text(true);
+    Ok(reader)
+}
+
+//Push an edge, and its reverse when the file marks it undirected
+fn push_edge(edges:&mut Vec<(String,String)>, source:String, target:String, directed:bool){
+    if !directed && source != target{
+        edges.push((target.clone(), source.clone()));
+    }
+    edges.push((source, target));
+}
+
+impl Graph{
+    //Read a GraphML file; <data> entries named label, subject or features fill NodeData
+    pub fn from_graphml(path:impl AsRef<Path>) -> Result<Self,String>{
+        let path = path.as_ref();
+        let mut reader = xml_reader(path)?;
+        let mut buf = Vec::new();
+        let mut key_names = HashMap::<String,String>::new();
+        let mut directed_default = true;
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        let mut current_node: Option<RawNode> = None;
+        let mut current_key: Option<String> = None;
+        let mut text = String::new();
+        loop{
+            let event = reader.read_event_into(&mut buf).map_err(|e| format!("Invalid GraphML in {}: {}", path.display(), e))?;
+            match event{
+                Event::Start(ref e) | Event::Empty(ref e) => {
+                    let is_empty = matches!(event, Event::Empty(_));
+                    match e.local_name().as_ref(){
+                        b"key" => {
+                            let id = required_attribute(e, b"id")?;
+                            let name = attribute(e, b"attr.name")?.unwrap_or_else(|| id.clone());
+                            key_names.insert(id, name);
+                        },
+                        b"graph" => directed_default = attribute(e, b"edgedefault")?.as_deref() != Some("undirected"),
+                        b"node" => {
+                            let node = RawNode::new(required_attribute(e, b"id")?);
+                            if is_empty { nodes.push(node) } else { current_node = Some(node) }
+                        },
+                        b"edge" => {
+                            let directed = match attribute(e, b"directed")?.as_deref(){
+                                Some(value) => value == "true",
+                                None => directed_default
+                            };
+                            push_edge(&mut edges, required_attribute(e, b"source")?, required_attribute(e, b"target")?, directed);
+                        },
+                        b"data" if !is_empty => {
+                            current_key = Some(required_attribute(e, b"key")?);
+                            text.clear();
+                        },
+                        _ => {}
+                    }
+                },
+                Event::Text(e) if current_key.is_some() => text.push_str(&e.unescape().map_err(|e| e.to_string())?),
+                Event::CData(e) if current_key.is_some() => text.push_str(&String::from_utf8_lossy(&e)),
+                Event::End(e) => match e.local_name().as_ref(){
+                    b"data" => {
+                        if let (Some(key), Some(node)) = (current_key.take(), current_node.as_mut()){
+                            let name = key_names.get(&key).cloned().unwrap_or(key);
+                            node.set_attribute(&name, text.clone());
+                        }
+                    },
+                    b"node" => {
+                        if let Some(node) = current_node.take(){
+                            nodes.push(node);
+                        }
+                    },
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(build_graph(nodes, &edges))
+    }
+
+    //Read a GEXF file; the node label field and attributes titled subject or features fill NodeData
+    pub fn from_gexf(path:impl AsRef<Path>) -> Result<Self,String>{
+        let path = path.as_ref();
+        let mut reader = xml_reader(path)?;
+        let mut buf = Vec::new();
+        let mut attribute_titles = HashMap::<String,String>::new();
+        let mut in_node_attributes = false;
+        let mut directed_default = true;
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        let mut current_node: Option<RawNode> = None;
+        loop{
+            let event = reader.read_event_into(&mut buf).map_err(|e| format!("Invalid GEXF in {}: {}", path.display(), e))?;
+            match event{
+                Event::Start(ref e) | Event::Empty(ref e) => {
+                    let is_empty = matches!(event, Event::Empty(_));
+                    match e.local_name().as_ref(){
+                        b"graph" => directed_default = attribute(e, b"defaultedgetype")?.as_deref() != Some("undirected"),
+                        b"attributes" => in_node_attributes = attribute(e, b"class")?.as_deref() == Some("node") && !is_empty,
+                        b"attribute" if in_node_attributes => {
+                            let id = required_attribute(e, b"id")?;
+                            let title = attribute(e, b"title")?.unwrap_or_else(|| id.clone());
+                            attribute_titles.insert(id, title);
+                        },
+                        b"node" => {
+                            let mut node = RawNode::new(required_attribute(e, b"id")?);
+                            node.label = attribute(e, b"label")?;
+                            if is_empty { nodes.push(node) } else { current_node = Some(node) }
+                        },
+                        b"attvalue" => {
+                            if let Some(node) = current_node.as_mut(){
+                                let key = required_attribute(e, b"for")?;
+                                let name = attribute_titles.get(&key).cloned().unwrap_or(key);
+                                //The label field wins over an attribute that happens to be titled label
+                                if name.to_lowercase() != "label" || node.label.is_none(){
+                                    node.set_attribute(&name, attribute(e, b"value")?.unwrap_or_default());
+                                }
+                            }
+                        },
+                        b"edge" => {
+                            let directed = match attribute(e, b"type")?.as_deref(){
+                                Some(kind) => kind != "undirected",
+                                None => directed_default
+                            };
+                            push_edge(&mut edges, required_attribute(e, b"source")?, required_attribute(e, b"target")?, directed);
+                        },
+                        _ => {}
+                    }
+                },
+                Event::End(e) => match e.local_name().as_ref(){
+                    b"attributes" => in_node_attributes = false,
+                    b"node" => {
+                        if let Some(node) = current_node.take(){
+                            nodes.push(node);
+                        }
+                    },
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(build_graph(nodes, &edges))
+    }
+
+    //Read a whitespace separated "source target" edge list (SNAP style), skipping blank lines and # or % comments.
+    //Undirected lists get each edge added in both directions.
+    pub fn from_edge_list(path:impl AsRef<Path>, directed:bool) -> Result<Self,String>{
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+        let mut edges = Vec::new();
+        for (line_no, line) in BufReader::new(file).lines().enumerate(){
+            let line = line.map_err(|e| e.to_string())?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('%'){
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next()){
+                (Some(source), Some(target)) => push_edge(&mut edges, source.to_string(), target.to_string(), directed),
+                _ => return Err(format!("Line {} of {} does not contain two node ids", line_no + 1, path.display()))
+            }
+        }
+        Ok(build_graph(Vec::new(), &edges))
+    }
+
+    //Read a Matrix Market coordinate file. Rows and columns become node ids 1..=n, every nonzero entry is a
+    //citation from row to column, and symmetric matrices get both directions.
+    pub fn from_matrix_market(path:impl AsRef<Path>) -> Result<Self,String>{
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+        let mut lines = contents.lines();
+        let header = lines.next().unwrap_or("").to_lowercase();
+        let header_fields: Vec<&str> = header.split_whitespace().collect();
+        if header_fields.len() < 5 || header_fields[0] != "%%matrixmarket" || header_fields[1] != "matrix"{
+            return Err(format!("{} does not start with a %%MatrixMarket matrix header", path.display()));
+        }
+        if header_fields[2] != "coordinate"{
+            return Err(format!("Only coordinate Matrix Market files are supported, {} is {}", path.display(), header_fields[2]));
+        }
+        let pattern = header_fields[3] == "pattern";
+        let directed = header_fields[4] == "general";
+
+        let mut lines = lines.map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('%'));
+        let size: Vec<usize> = lines.next()
+            .ok_or_else(|| format!("{} has no size line", path.display()))?
+            .split_whitespace()
+            .map(|x| x.parse::<usize>().map_err(|e| e.to_string()))
+            .collect::<Result<_,_>>()?;
+        if size.len() != 3{
+            return Err(format!("Size line of {} should be \"rows columns entries\"", path.display()));
+        }
+        let (rows, columns) = (size[0], size[1]);
+        let n = rows.max(columns);
+        if n > MAX_MATRIX_MARKET_NODES{
+            return Err(format!("{} declares a {} x {} matrix, more than the {} papers supported", path.display(), rows, columns, MAX_MATRIX_MARKET_NODES));
+        }
+        let nodes: Vec<RawNode> = (1..=n).map(|id| RawNode::new(id.to_string())).collect();
+
+        //The entry count on the size line is not trusted for a reservation
+        let mut edges = Vec::new();
+        for line in lines{
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 2{
+                return Err(format!("Entry \"{}\" in {} is missing a row or column", line, path.display()));
+            }
+            let row = fields[0].parse::<usize>().map_err(|e| e.to_string())?;
+            let column = fields[1].parse::<usize>().map_err(|e| e.to_string())?;
+            if row == 0 || column == 0 || row > rows || column > columns{
+                return Err(format!("Entry \"{}\" in {} is outside the {} x {} matrix", line, path.display(), rows, columns));
+            }
+            //Explicit zeros are stored entries but not citations
+            if !pattern && fields.get(2).and_then(|v| v.parse::<f64>().ok()) == Some(0.0){
+                continue;
+            }
+            push_edge(&mut edges, row.to_string(), column.to_string(), directed);
+        }
+        Ok(build_graph(nodes, &edges))
+    }
+}
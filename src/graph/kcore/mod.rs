@@ -0,0 +1,114 @@
+use crate::graph::*;
+use crate::graph::component_functions::get_component_scale;
+
+//Which degree has to reach k for a paper to stay in the k-core
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CoreDegree{
+    //Distinct neighbours ignoring citation direction
+    Undirected,
+    //Distinct papers inside the core citing it
+    In,
+    //Distinct papers inside the core it cites
+    Out
+}
+
+//Shape of the k-core for one k
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreLevel{
+    pub k:usize,
+    pub nodes:usize,
+    pub num_components:usize,
+    //Fraction of the k-core inside its largest component, from get_component_scale
+    pub giant_share:f64
+}
+
+impl Graph{
+    //Largest k such that the node belongs to the k-core, using the Batagelj-Zaversnik bucket algorithm
+    pub fn coreness(&self, degree:CoreDegree) -> Vec<usize>{
+        //affects[v] lists the nodes whose degree drops when v is peeled off. A repeated citation between the same
+        //pair of papers is listed once, so all three degrees count distinct neighbours.
+        let mut affects: AdjacencyList = match degree{
+            CoreDegree::Undirected => self.undirected_adjacency(),
+            CoreDegree::In => self.outedges.iter().enumerate()
+                .map(|(v, neighbors)| neighbors.iter().copied().filter(|w| *w != v).collect())
+                .collect(),
+            CoreDegree::Out => {
+                let mut citers: AdjacencyList = vec![vec![];self.n];
+                for (v, neighbors) in self.outedges.iter().enumerate(){
+                    for w in neighbors.iter().filter(|w| **w != v){
+                        citers[*w].push(v);
+                    }
+                }
+                citers
+            }
+        };
+        for neighbors in affects.iter_mut(){
+            neighbors.sort_unstable();
+            neighbors.dedup();
+        }
+        let mut degrees = vec![0;self.n];
+        for neighbors in affects.iter(){
+            for w in neighbors.iter(){
+                degrees[*w] += 1;
+            }
+        }
+
+        //Nodes sorted by degree, with bin_start[d] the first position holding degree d
+        let max_degree = degrees.iter().copied().max().unwrap_or(0);
+        let mut bin_start = vec![0;max_degree + 2];
+        for d in degrees.iter(){
+            bin_start[*d + 1] += 1;
+        }
+        for d in 1..bin_start.len(){
+            bin_start[d] += bin_start[d - 1];
+        }
+        let mut order = vec![0;self.n];
+        let mut position = vec![0;self.n];
+        let mut next_slot = bin_start.clone();
+        for v in 0..self.n{
+            position[v] = next_slot[degrees[v]];
+            order[position[v]] = v;
+            next_slot[degrees[v]] += 1;
+        }
+
+        for i in 0..self.n{
+            let v = order[i];
+            for w in affects[v].iter(){
+                if degrees[*w] > degrees[v]{
+                    //Swap w to the front of its bin, then shrink the bin past it
+                    let d = degrees[*w];
+                    let first = order[bin_start[d]];
+                    if first != *w{
+                        order.swap(position[*w], bin_start[d]);
+                        position[first] = position[*w];
+                        position[*w] = bin_start[d];
+                    }
+                    bin_start[d] += 1;
+                    degrees[*w] -= 1;
+                }
+            }
+        }
+        degrees
+    }
+
+    //Subgraph of the papers with coreness of at least k
+    pub fn k_core(&self, k:usize, degree:CoreDegree) -> Graph{
+        let keep: Vec<bool> = self.coreness(degree).iter().map(|core| *core >= k).collect();
+        self.induced_subgraph(&keep)
+    }
+
+    //Size and connectivity of every k-core from k = 0 up to the largest non-empty one
+    pub fn core_profile(&self, degree:CoreDegree) -> Vec<CoreLevel>{
+        let coreness = self.coreness(degree);
+        let max_core = coreness.iter().copied().max().unwrap_or(0);
+        (0..=max_core).map(|k| {
+            let keep: Vec<bool> = coreness.iter().map(|core| *core >= k).collect();
+            let core = self.induced_subgraph(&keep);
+            let (component, num_components) = core.connected_components();
+            let scale = get_component_scale(&component, num_components, true);
+            CoreLevel{k, nodes:core.n, num_components, giant_share:scale.get(1).copied().unwrap_or(0.0)}
+        }).collect()
+    }
+}
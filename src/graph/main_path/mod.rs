@@ -0,0 +1,146 @@
+use crate::graph::*;
+
+//How many search paths an edge lies on. A source is a paper nobody cites and a sink a paper citing nothing.
+//  Spc:  paths from a source to a sink
+//  Splc: paths from a source to any paper
+//  Spnp: paths from any paper to any paper
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TraversalWeight{
+    Spc,
+    Splc,
+    Spnp
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MainPathSearch{
+    //Start from the heaviest edges leaving a source and keep following the heaviest citation(s), ties included
+    Local,
+    //The single source to sink path with the largest total weight
+    Global
+}
+
+//An extracted main path
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MainPath{
+    //Only the papers and citations on the path, ready for visualize_connectivity or the exporters
+    pub graph:Graph,
+    //(citing, cited, weight) with original ids, in the order they were traversed
+    pub citations:Vec<(usize,usize,f64)>,
+    pub total_weight:f64
+}
+
+impl Graph{
+    //Search path weight of every citation, laid out like outedges (weights[v][i] belongs to v -> outedges[v][i]).
+    //Path counts grow exponentially with depth, so they are kept as f64. Cyclic graphs are rejected, see make_acyclic.
+    pub fn search_path_weights(&self, weight:TraversalWeight) -> Result<Vec<Vec<f64>>,String>{
+        let order = self.topological_sort().ok_or_else(|| format!(
+            "Main path analysis needs an acyclic graph but {} strongly connected components contain cycles, see make_acyclic",
+            self.cyclic_components().len()))?;
+        //Paths ending at each node: from a source for Spc/Splc, from any node for Spnp
+        let count_trivial = weight == TraversalWeight::Spnp;
+        let mut from_above = vec![0.0;self.n];
+        for v in order.iter(){
+            if count_trivial || from_above[*v] == 0.0{
+                from_above[*v] += 1.0;
+            }
+            for w in self.outedges[*v].iter(){
+                from_above[*w] += from_above[*v];
+            }
+        }
+        //Paths starting at each node: to a sink for Spc, to any node for Splc/Spnp
+        let count_trivial = weight != TraversalWeight::Spc;
+        let mut from_below = vec![0.0;self.n];
+        for v in order.iter().rev(){
+            let below: f64 = self.outedges[*v].iter().map(|w| from_below[*w]).sum();
+            from_below[*v] = if count_trivial || self.outedges[*v].is_empty() { below + 1.0 } else { below };
+        }
+        Ok(self.outedges.iter().enumerate()
+            .map(|(v, neighbors)| neighbors.iter().map(|w| from_above[v] * from_below[*w]).collect())
+            .collect())
+    }
+
+    pub fn main_path(&self, weight:TraversalWeight, search:MainPathSearch) -> Result<MainPath,String>{
+        let weights = self.search_path_weights(weight)?;
+        let edges = match search{
+            MainPathSearch::Local => self.local_main_path(&weights),
+            MainPathSearch::Global => self.global_main_path(&weights)
+        };
+        let citations: Vec<(usize,usize,f64)> = edges.iter()
+            .map(|(v, i)| (self.reverse_map[v], self.reverse_map[&self.outedges[*v][*i]], weights[*v][*i]))
+            .collect();
+        let mapped_edges: Vec<Edge> = edges.iter().map(|(v, i)| (*v, self.outedges[*v][*i])).collect();
+        Ok(MainPath{
+            graph:self.edge_subgraph(&mapped_edges),
+            total_weight:citations.iter().map(|(_, _, weight)| weight).sum(),
+            citations
+        })
+    }
+
+    //Edges are (citing node, position in its outedges)
+    fn local_main_path(&self, weights:&[Vec<f64>]) -> Vec<(Vertex,usize)>{
+        let in_degrees = self.in_degrees();
+        let sources: Vec<Vertex> = (0..self.n).filter(|v| in_degrees[*v] == 0).collect();
+        let mut path = Vec::new();
+        let mut frontier = heaviest_edges(weights, &sources);
+        let mut visited = vec![false;self.n];
+        while !frontier.is_empty(){
+            let mut next = Vec::new();
+            for (v, i) in frontier{
+                path.push((v, i));
+                let w = self.outedges[v][i];
+                if !visited[w]{
+                    visited[w] = true;
+                    next.push(w);
+                }
+            }
+            //Each node on the path continues along its own heaviest citations
+            frontier = next.iter().flat_map(|v| heaviest_edges(weights, &[*v])).collect();
+        }
+        path
+    }
+
+    fn global_main_path(&self, weights:&[Vec<f64>]) -> Vec<(Vertex,usize)>{
+        let order = self.topological_sort().unwrap_or_default();
+        //best[v] is the heaviest path from v to a sink and the edge it starts with
+        let mut best: Vec<(f64,Option<usize>)> = vec![(0.0,None);self.n];
+        for v in order.iter().rev(){
+            for (i, w) in self.outedges[*v].iter().enumerate(){
+                let total = weights[*v][i] + best[*w].0;
+                if best[*v].1.is_none() || total > best[*v].0{
+                    best[*v] = (total, Some(i));
+                }
+            }
+        }
+        let in_degrees = self.in_degrees();
+        let start = (0..self.n)
+            .filter(|v| in_degrees[*v] == 0 && best[*v].1.is_some())
+            .fold(None, |start: Option<Vertex>, v| match start{
+                Some(s) if best[s].0 >= best[v].0 => Some(s),
+                _ => Some(v)
+            });
+        let mut path = Vec::new();
+        let mut current = start;
+        while let Some(v) = current{
+            current = best[v].1.map(|i| {
+                path.push((v, i));
+                self.outedges[v][i]
+            });
+        }
+        path
+    }
+}
+
+//All out-edges of `nodes` that share the largest weight among them
+fn heaviest_edges(weights:&[Vec<f64>], nodes:&[Vertex]) -> Vec<(Vertex,usize)>{
+    let max_weight = nodes.iter()
+        .flat_map(|v| weights[*v].iter())
+        .fold(f64::NEG_INFINITY, |max, weight| max.max(*weight));
+    nodes.iter()
+        .flat_map(|v| weights[*v].iter().enumerate().map(move |(i, weight)| (*v, i, *weight)))
+        .filter(|(_, _, weight)| *weight == max_weight)
+        .map(|(v, i, _)| (v, i))
+        .collect()
+}
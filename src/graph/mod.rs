@@ -1,17 +1,49 @@
 pub mod component_functions;
 pub mod visualization_support;
+pub mod export;
+pub mod import;
+pub mod report;
+pub mod paths;
+pub mod dag;
+pub mod main_path;
+pub mod coupling;
+pub mod kcore;
+pub mod clustering;
+pub mod cut_points;
+pub mod union_find;
+pub mod robustness;
+pub mod connectivity;
+pub mod cache;
+pub mod mutation;
+pub mod cleaning;
+pub mod streaming;
 
 use std::collections::{HashMap,VecDeque};
+use std::path::Path;
+use std::time::Instant;
 use plotters::prelude::*;
 use full_palette::*;
-use rand::Rng;
-type Vertex = usize;
-type Edge = (Vertex, Vertex);
-type AdjacencyList = Vec<Vec<Vertex>>;
-type Component = usize;
-
-#[derive(Debug,Clone)]
-#[allow(dead_code)] //Allowed for now until ML features get developed
+use visualization_support::{DrawStyle,RenderTimings};
+use cleaning::CleaningPolicy;
+use streaming::open_input;
+//Mapped node index, 0..n
+pub type Vertex = usize;
+//Citation as (citing, cited) mapped nodes
+pub type Edge = (Vertex, Vertex);
+pub type AdjacencyList = Vec<Vec<Vertex>>;
+//Component number, starting at 1
+pub type Component = usize;
+//Pixel position in a plot
+pub type Point = (i32, i32);
+//Node data by original id and original id by mapped node
+type NodeMaps = (HashMap<usize,NodeData>, HashMap<usize,usize>);
+
+//Below this many nodes the sequential BFS beats spinning up the thread pool
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_NODES: usize = 10_000;
+
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeData{
     pub mapped_node:usize,
     pub label:String,
@@ -20,15 +52,19 @@ pub struct NodeData{
 }
 //Function for NodeData to read from csv
 impl NodeData{
+    //Read a bracketed feature list such as "[0,1,0]"
+    pub fn parse_features(features:&str) -> Vec<u8>{
+        features
+            .trim_matches(|c| c == '[' || c == ']') // Remove the brackets
+            .split(',')                            // Split by comma
+            .filter_map(|x| x.trim().parse::<u8>().ok()) // Parse to u8 and filter out invalid entries
+            .collect()                             // Collect into a vector
+    }
     //Serialize the input String record
     fn read_strings(line:&csv::StringRecord,mapped_node:usize) -> Self{
         let label = String::from(&line[2]);
         let subject = String::from(&line[3]);
-        let features: Vec<u8> = line[4]
-            .trim_matches(|c| c == '[' || c == ']') // Remove the brackets
-            .split(',')                            // Split by comma
-            .filter_map(|x| x.parse::<u8>().ok()) // Parse to usize and filter out invalid entries
-            .collect();                            // Collect into a vector
+        let features = NodeData::parse_features(&line[4]);
         NodeData{
             mapped_node,
             label,
@@ -37,73 +73,138 @@ impl NodeData{
         }
     }
 }
-#[derive(Debug)]
+#[derive(Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+//The fields are private so that only the constructors and the mutation methods can change them, which keeps
+//n, outedges, node_data and reverse_map consistent. Read them through the accessors of the same name.
 pub struct Graph{
-    pub n: usize,
-    pub outedges: AdjacencyList,
-    pub node_data:HashMap<usize,NodeData>,
-    pub reverse_map:HashMap<usize,usize>
+    n: usize,
+    outedges: AdjacencyList,
+    node_data:HashMap<usize,NodeData>,
+    reverse_map:HashMap<usize,usize>
 }
 impl Graph{
     //Create graph from directed edges
-    fn create_directed(n: usize, edges: &Vec<Edge>,node_data:HashMap<usize,NodeData>,reverse_hash:HashMap<usize,usize>) -> Self{
+    fn create_directed(n: usize, edges: &[Edge],node_data:HashMap<usize,NodeData>,reverse_hash:HashMap<usize,usize>) -> Self{
         let mut adj_list:AdjacencyList = vec![vec![];n];
         for (v, w) in edges.iter(){
             adj_list[*v].push(*w);
         }
         //We allow the data to be moved here, since we want it to live in the object anyways
-        Graph{n,outedges:adj_list,node_data:node_data,reverse_map:reverse_hash}
+        Graph{n,outedges:adj_list,node_data,reverse_map:reverse_hash}
+    }
+    //Read the input csv files. Every citation is kept as it is and an edge to a paper missing from the
+    //nodes file is an error; see from_csvs_cleaned for other policies.
+    pub fn from_csvs(edge_path:impl AsRef<Path>, node_path:impl AsRef<Path>) -> Result<Self,String>{
+        Graph::from_csvs_cleaned(edge_path, node_path, &CleaningPolicy::default()).map(|(graph, _)| graph)
     }
-    //Read the input csv files
-    pub fn from_csvs(edge_path:&str, node_path:&str) -> Result<Self,String>{
+
+    //Node rows keyed by original id, the reverse map and the original ids of repeated rows (the first row wins)
+    fn read_node_csv(node_path:&Path) -> Result<(NodeMaps, Vec<usize>),String>{
         let mut node_rdr = csv::ReaderBuilder::new()
             .has_headers(true)
             .flexible(true)
-            .from_path(node_path).expect("Expected a valid file path");
+            .from_reader(open_input(node_path)?);
         //Iterate over each record, creating a corresponding map for it
         let mut node_data= HashMap::<usize,NodeData>::new(); //Can be used to search a node and get data
         let mut reverse_hash = HashMap::<usize,usize>::new(); //Can be used to undo the node mapping
-        for (index, record) in node_rdr.records().enumerate(){
-            let r = record.expect("A CSV Line In node_path");
-            let data = NodeData::read_strings(&r, index);
-            let node_id = String::from(&r[1]).parse::<usize>().unwrap();
-            node_data.insert(node_id,data);
+        let mut repeated = Vec::new();
+        for record in node_rdr.records(){
+            let r = record.map_err(|e| format!("Could not read {}: {}", node_path.display(), e))?;
+            let line = r.position().map_or(0, |position| position.line());
+            let node_id = r.get(1).and_then(|id| id.trim().parse::<usize>().ok()).filter(|_| r.len() >= 5)
+                .ok_or_else(|| format!("Line {} of {} does not contain a numeric node id, label, subject and features", line, node_path.display()))?;
+            if node_data.contains_key(&node_id){
+                repeated.push(node_id);
+                continue;
+            }
+            let index = node_data.len();
+            node_data.insert(node_id,NodeData::read_strings(&r, index));
             reverse_hash.insert(index, node_id);
         }
+        Ok(((node_data, reverse_hash), repeated))
+    }
 
+    //Citations as (citing, cited) original ids in file order
+    fn read_edge_csv(edge_path:&Path) -> Result<Vec<(usize,usize)>,String>{
         let mut edge_rdr = csv::ReaderBuilder::new()
             .has_headers(true)
             .flexible(true)
-            .from_path(edge_path).expect("Expected a valid file path");
-        let mut edges:Vec<Edge> = Vec::new();
+            .from_reader(open_input(edge_path)?);
+        let mut edges = Vec::new();
         for record in edge_rdr.records(){
-            let r = record.expect("A CSV Line in edge_path");
-            //Get the node_id and its mapped index, raise an error if not found
-            let node_id = String::from(&r[1]).parse::<usize>().unwrap();
-            let node_id_adjusted = match node_data.get(&node_id){
-                Some(node) => node.mapped_node,
-                None => return Err(format!("Node ID {} not found in node data",node_id).into())
-            };
-
-            //Get the target_id and its mapped index, raise an error if not found
-            let target_id = String::from(&r[2]).parse::<usize>().unwrap();
-            let target_id_adjusted = match node_data.get(&target_id){
-                Some(node) => node.mapped_node,
-                None => return Err(format!("Target Node ID {} not found in node data",target_id).into())
-            };
-
-            edges.push((node_id_adjusted,target_id_adjusted));
+            let r = record.map_err(|e| format!("Could not read {}: {}", edge_path.display(), e))?;
+            let line = r.position().map_or(0, |position| position.line());
+            let id = |field:usize| r.get(field).and_then(|id| id.trim().parse::<usize>().ok())
+                .ok_or_else(|| format!("Line {} of {} does not contain two numeric node ids", line, edge_path.display()));
+            edges.push((id(1)?, id(2)?));
         }
-        
-        return Ok(Graph::create_directed(node_data.len(),&edges,node_data,reverse_hash));
+        Ok(edges)
     }
-    
+
+    //Number of papers; mapped nodes are 0..n
+    pub fn n(&self) -> usize{
+        self.n
+    }
+    //Cited mapped nodes of every mapped node, in file order and including repeated citations
+    pub fn outedges(&self) -> &AdjacencyList{
+        &self.outedges
+    }
+    //Paper data keyed by original id
+    pub fn node_data(&self) -> &HashMap<usize,NodeData>{
+        &self.node_data
+    }
+    //Original id of every mapped node
+    pub fn reverse_map(&self) -> &HashMap<usize,usize>{
+        &self.reverse_map
+    }
+
     pub fn calc_num_edges(&self) -> usize{
         let mut n_edges = 0;
         for outedge in self.outedges.iter(){
             n_edges += outedge.len();
         }
-        return n_edges;
+        n_edges
+    }
+    //Number of citations each paper receives (edges pointing into it)
+    pub fn in_degrees(&self) -> Vec<usize>{
+        let mut degrees = vec![0;self.n];
+        for outedge in self.outedges.iter(){
+            for w in outedge{
+                degrees[*w] += 1;
+            }
+        }
+        degrees
+    }
+    //Number of references each paper makes (edges leaving it)
+    pub fn out_degrees(&self) -> Vec<usize>{
+        self.outedges.iter().map(|outedge| outedge.len()).collect()
+    }
+    //Neighbours of every node ignoring citation direction, without duplicates or self-loops
+    pub fn undirected_adjacency(&self) -> AdjacencyList{
+        let mut adj_list:AdjacencyList = vec![vec![];self.n];
+        for (v, outedge) in self.outedges.iter().enumerate(){
+            for w in outedge{
+                if v != *w{
+                    adj_list[v].push(*w);
+                    adj_list[*w].push(v);
+                }
+            }
+        }
+        for neighbors in adj_list.iter_mut(){
+            neighbors.sort_unstable();
+            neighbors.dedup();
+        }
+        adj_list
+    }
+    //Total degree (citations made plus received) divided by the n - 1 possible neighbours
+    pub fn degree_centrality(&self) -> Vec<f64>{
+        if self.n < 2{
+            return vec![0.0;self.n];
+        }
+        self.in_degrees().iter().zip(self.out_degrees().iter())
+            .map(|(in_degree, out_degree)| (in_degree + out_degree) as f64 / (self.n - 1) as f64)
+            .collect()
     }
 
     pub fn calculate_subgraphs(&self) -> Vec<(String,Self)>{
@@ -114,7 +215,7 @@ impl Graph{
         //For each point in the grpah
         for i in 0..self.n{
             let original_node = self.reverse_map.get(&i).unwrap(); //Get the original node
-            let node_data = self.node_data.get(&original_node).unwrap(); //Get the original node's data
+            let node_data = self.node_data.get(original_node).unwrap(); //Get the original node's data
             let subject = node_data.subject.clone(); //Get the subject of the original node
             let mut outedge = self.outedges[i].clone(); //Get the edges from the mapped_node
             outedge = outedge.iter().map(|edge| *self.reverse_map.get(edge).unwrap()).collect();
@@ -136,45 +237,151 @@ impl Graph{
             for outedge in adj_list.iter(){
                 let mut adjusted_outedge:Vec<usize> = Vec::new();
                 for edge in outedge{
-                    if node_data.contains_key(&edge){
-                        adjusted_outedge.push(node_data.get(&edge).unwrap().mapped_node);
+                    if node_data.contains_key(edge){
+                        adjusted_outedge.push(node_data.get(edge).unwrap().mapped_node);
                     }
                 }
                 adjusted_outedges.push(adjusted_outedge);
             }
             output.push((subject,Graph{n,outedges:adjusted_outedges,node_data,reverse_map}));
         }
-        return output
+        output
+    }
+
+    //Graph of the nodes with keep[v] set and the citations between them, renumbered in mapped order
+    pub fn induced_subgraph(&self, keep:&[bool]) -> Self{
+        let mut new_index: Vec<Option<Vertex>> = vec![None;self.n];
+        let mut node_data = HashMap::new();
+        let mut reverse_map = HashMap::new();
+        for v in (0..self.n).filter(|v| keep[*v]){
+            let mapped_node = reverse_map.len();
+            new_index[v] = Some(mapped_node);
+            let original_node = self.reverse_map[&v];
+            let mut adjusted_node_data = self.node_data[&original_node].clone();
+            adjusted_node_data.mapped_node = mapped_node;
+            node_data.insert(original_node, adjusted_node_data);
+            reverse_map.insert(mapped_node, original_node);
+        }
+        let outedges: AdjacencyList = (0..self.n).filter(|v| keep[*v])
+            .map(|v| self.outedges[v].iter().filter_map(|w| new_index[*w]).collect())
+            .collect();
+        Graph{n:reverse_map.len(), outedges, node_data, reverse_map}
+    }
+
+    //Graph made of the given (mapped) edges and only the nodes they touch, renumbered in order of first appearance
+    pub fn edge_subgraph(&self, edges:&[Edge]) -> Self{
+        let mut new_index: HashMap<Vertex,Vertex> = HashMap::new();
+        let mut node_data = HashMap::new();
+        let mut reverse_map = HashMap::new();
+        for (v, w) in edges.iter(){
+            for node in [*v, *w]{
+                if new_index.contains_key(&node){
+                    continue;
+                }
+                let mapped_node = new_index.len();
+                new_index.insert(node, mapped_node);
+                let original_node = self.reverse_map[&node];
+                let mut adjusted_node_data = self.node_data[&original_node].clone();
+                adjusted_node_data.mapped_node = mapped_node;
+                node_data.insert(original_node, adjusted_node_data);
+                reverse_map.insert(mapped_node, original_node);
+            }
+        }
+        let mut outedges: AdjacencyList = vec![vec![];new_index.len()];
+        for (v, w) in edges.iter(){
+            outedges[new_index[v]].push(new_index[w]);
+        }
+        Graph{n:new_index.len(), outedges, node_data, reverse_map}
     }
 
+    //Components numbered from 1 in order of their smallest mapped node. With the `parallel` feature, graphs of
+    //PARALLEL_MIN_NODES or more run label propagation on the rayon pool, which gives exactly the same numbering.
     pub fn connected_components(&self) -> (Vec<Option<Component>>, usize){
+        #[cfg(feature = "parallel")]
+        if self.n >= PARALLEL_MIN_NODES{
+            return self.connected_components_parallel();
+        }
+        self.connected_components_sequential()
+    }
+
+    pub fn connected_components_sequential(&self) -> (Vec<Option<Component>>, usize){
         let mut component:Vec<Option<Component>> = vec![None;self.n];
         let mut component_count = 0;
+        //Weak components: a citation links two papers whichever way it points
+        let adj_list = self.undirected_adjacency();
+        for v in 0..self.n{
+            if component[v].is_none(){
+                component_count += 1;
+                component_functions::mark_component_bfs(v, &adj_list, &mut component, component_count);
+            }
+        }
+        (component, component_count)
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn connected_components_parallel(&self) -> (Vec<Option<Component>>, usize){
+        let labels = component_functions::propagate_min_labels(&self.undirected_adjacency());
+        //Every component is labelled by its smallest node, so numbering the roots in order matches the BFS
+        let mut numbers: Vec<Option<Component>> = vec![None;self.n];
+        let mut component_count = 0;
         for v in 0..self.n{
-            if let None = component[v]{
+            if labels[v] == v{
                 component_count += 1;
-                component_functions::mark_component_bfs(v, self, &mut component, component_count);
+                numbers[v] = Some(component_count);
             }
         }
-        return (component, component_count)
+        (labels.iter().map(|root| numbers[*root]).collect(), component_count)
     }
     //Create a png graph of the connected components of the graph
     pub fn visualize_connectivity(
         &self,
-        output_file: &str,
+        output_file: impl AsRef<Path>,
         biggest_circle: f64,
         output_size:(u32,u32),
         title:&str
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let root = BitMapBackend::new(output_file, output_size).into_drawing_area();
+        self.visualize_connectivity_styled(output_file, biggest_circle, output_size, title, &DrawStyle::default())
+    }
+    //Same as visualize_connectivity, but node sizes and edge drawing follow the given style
+    pub fn visualize_connectivity_styled(
+        &self,
+        output_file: impl AsRef<Path>,
+        biggest_circle: f64,
+        output_size:(u32,u32),
+        title:&str,
+        style:&DrawStyle
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        //Run algorithm
+        let (components,num_components) = self.connected_components();
+        // Assign positions to nodes, clustered by components in circles
+        let positions = visualization_support::layout_components(&components, num_components, visualization_support::DRAWING_AREA, biggest_circle);
+        self.render_connectivity(&components, num_components, &positions, output_file, output_size, title, style)?;
+        Ok(())
+    }
+    //Draw the graph from a precomputed component assignment and layout, batching all primitives of a kind into one series
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_connectivity(
+        &self,
+        components:&[Option<Component>],
+        num_components:usize,
+        positions:&[Option<(i32,i32)>],
+        output_file: impl AsRef<Path>,
+        output_size:(u32,u32),
+        title:&str,
+        style:&DrawStyle
+    ) -> Result<RenderTimings, Box<dyn std::error::Error>> {
+        if components.len() != self.n || positions.len() != self.n{
+            return Err(format!("Expected a component and a position for each of the {} nodes, got {} and {}", self.n, components.len(), positions.len()).into());
+        }
+        let start = Instant::now();
+        let root = BitMapBackend::new(output_file.as_ref(), output_size).into_drawing_area();
         root.fill(&GREY_100)?;
     
         // Title for the graph
         let root_area = root.titled(title, ("helvetica-bold", 45))?;
     
         // Determine the bounds of the drawing area
-        let drawing_area = (-500,500,-500,500);
-        let (x_min, x_max, y_min, y_max) = drawing_area;
+        let (x_min, x_max, y_min, y_max) = visualization_support::DRAWING_AREA;
 
         let vertices = self.n;
         let edges = self.calc_num_edges();
@@ -182,74 +389,124 @@ impl Graph{
         let mut cc = ChartBuilder::on(&root_area)
             .margin(10)
             .caption(
-                &format!("Distribution of citation network by connected component (V = {}, E = {})",vertices,edges), 
+                format!("Distribution of citation network by connected component (V = {}, E = {})",vertices,edges), 
                 TextStyle::from(("helvetica", 30).into_font()).color(&GREY_600))
             .build_cartesian_2d(x_min..x_max, y_min..y_max)?;
     
         cc.configure_mesh().disable_mesh().draw()?;
+        let setup = start.elapsed();
+
+        let radii = visualization_support::node_radii(self, &style.node_sizing, style.radius_range);
+        let edge_color = CYAN_700.mix(style.edge_alpha);
     
-        //Run algorithm
-        let (components,num_components) = self.connected_components();
-        
-        // Step 1: Assign positions to nodes, clustered by components in circles
-        let range_hash = visualization_support::get_graph_dimensions(&components, num_components, drawing_area, biggest_circle,50.0);
-        let mut rng = rand::thread_rng();
-        let mut positions: HashMap<usize, (i32, i32)> = HashMap::new();
-    
-        for (i, component) in components.iter().enumerate() {
-            if let Some(comp) = component {
-                //Switch back from 1 based to 0 based index
-                if let Some((center, radius)) = range_hash.get(&(comp-1)) {
-                    let (center_x, center_y) = *center;
-    
-                    // Generate random position within the circle using polar coordinates
-                    let random_angle = rng.gen_range(0.0..(2.0 * std::f64::consts::PI)); // Random angle
-                    let random_radius = rng.gen_range(0.0..=*radius); // Random radius (uniform distribution)
-    
-                    let offset_x = random_radius * random_angle.cos();
-                    let offset_y = random_radius * random_angle.sin();
-    
-                    let node_x = (center_x + offset_x) as i32;
-                    let node_y = (center_y + offset_y) as i32;
-                    positions.insert(i, (node_x, node_y));
-                }
-            }
-        }
-    
-        // Step 2: Draw edges between nodes
+        // Step 1: Collect every drawable edge, keeping a random sample once the threshold is passed
+        let edge_start = Instant::now();
+        let mut segments: Vec<(Point,Point,Vertex)> = Vec::with_capacity(edges);
         for (node, neighbors) in self.outedges.iter().enumerate() {
-            if let Some(&(x1, y1)) = positions.get(&node) {
+            if let Some((x1, y1)) = positions[node] {
                 for &neighbor in neighbors {
-                    if let Some(&(x2, y2)) = positions.get(&neighbor) {
-                        cc.draw_series(LineSeries::new(vec![(x1, y1), (x2, y2)], &CYAN_100))?;
+                    if let Some((x2, y2)) = positions[neighbor] {
+                        segments.push(((x1, y1), (x2, y2), neighbor));
                     }
                 }
             }
         }
-    
-        // Step 3: Draw nodes as circles with color based on their component
-        for (node, &(x, y)) in positions.iter() {
-            if let Some(component) = components[*node] {
-                // Get the color for the component
-                let color = visualization_support::get_color_from_gradient(component,num_components);
-    
-                // Draw the node with the assigned color
-                cc.draw_series(std::iter::once(Circle::new((x, y), 5, color.filled())))?;
+        let drawable_edges = segments.len();
+        if let Some(max_edges) = style.max_edges{
+            if segments.len() > max_edges{
+                let mut rng = rand::thread_rng();
+                let keep = rand::seq::index::sample(&mut rng, segments.len(), max_edges);
+                segments = keep.iter().map(|index| segments[index]).collect();
             }
         }
+
+        // Step 2: Draw edges, as arrows pointing from the citing paper to the cited paper
+        if style.arrows{
+            //Stop the shaft at the edge of the cited node so the head stays visible
+            let shafts: Vec<(Point,Point)> = segments.iter()
+                .map(|(from, to, target)| (*from, visualization_support::shorten_segment(*from, *to, radii[*target] as f64)))
+                .collect();
+            cc.draw_series(shafts.iter().map(|(from, tip)| PathElement::new(vec![*from, *tip], edge_color)))?;
+            cc.draw_series(shafts.iter()
+                .filter_map(|(from, tip)| visualization_support::arrow_head(*from, *tip, style.arrow_size))
+                .map(|head| Polygon::new(head.to_vec(), edge_color.filled())))?;
+        }
+        else{
+            cc.draw_series(segments.iter().map(|(from, to, _)| PathElement::new(vec![*from, *to], edge_color)))?;
+        }
+        //Highlighted edges are always drawn, even when sampled out above
+        if !style.highlight_edges.is_empty(){
+            let highlighted: std::collections::HashSet<Edge> = style.highlight_edges.iter()
+                .map(|(v, w)| (*v.min(w), *v.max(w)))
+                .collect();
+            cc.draw_series(self.outedges.iter().enumerate()
+                .flat_map(|(v, neighbors)| neighbors.iter().map(move |w| (v, *w)))
+                .filter(|(v, w)| highlighted.contains(&(*v.min(w), *v.max(w))))
+                .filter_map(|(v, w)| Some(PathElement::new(vec![positions[v]?, positions[w]?], RED_700.stroke_width(3)))))?;
+        }
+        let edge_time = edge_start.elapsed();
     
-        Ok(())
+        // Step 3: Draw nodes as circles with color based on their component
+        let node_start = Instant::now();
+        cc.draw_series(positions.iter().enumerate().filter_map(|(node, position)| {
+            let (x, y) = (*position)?;
+            let component = components[node]?;
+            // Get the color for the component
+            let color = visualization_support::get_color_from_gradient(component,num_components);
+            Some(Circle::new((x, y), radii[node], color.filled()))
+        }))?;
+        cc.draw_series(style.highlight_nodes.iter().filter_map(|node| {
+            let (x, y) = positions[*node]?;
+            Some(Circle::new((x, y), radii[*node] + 4, RED_700.stroke_width(3)))
+        }))?;
+        root.present()?;
+        let node_time = node_start.elapsed();
+    
+        Ok(RenderTimings{
+            setup,
+            edges:edge_time,
+            nodes:node_time,
+            total:start.elapsed(),
+            edges_drawn:segments.len(),
+            edges_skipped:drawable_edges - segments.len()
+        })
     }
 }
 //Unit tests for module features
 #[cfg(test)]
 mod tests{
-    use crate::*;
+    use super::*;
+    use crate::graph::component_functions::*;
+    use crate::graph::visualization_support::*;
+    use crate::graph::export::*;
+    use crate::graph::report::*;
+    use crate::graph::paths::*;
+    use crate::graph::main_path::*;
+    use crate::graph::kcore::*;
+    use crate::graph::cut_points::*;
+    use crate::graph::robustness::*;
+    use crate::graph::union_find::*;
+    use crate::graph::connectivity::*;
+    use crate::graph::cache::*;
+    use crate::graph::cleaning::*;
+    use std::path::{Path,PathBuf};
+
+    fn test_data(file:&str) -> PathBuf{
+        Path::new("tests").join("test_data").join(file)
+    }
+
+    //Generated files are not tracked, see .gitignore
+    fn test_output(file:&str) -> PathBuf{
+        let dir = Path::new("tests").join("test_output");
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(file)
+    }
+
     #[test]
     fn test_connected_components_single_component() {
         let graph = Graph::from_csvs(
-            "tests\\test_data\\edges_single_component.csv",
-            "tests\\test_data\\nodes_single_component.csv"
+            test_data("edges_single_component.csv"),
+            test_data("nodes_single_component.csv")
         ).unwrap();
         let (_component, num_components) = graph.connected_components();
         assert_eq!(num_components, 1);  // There should be 1 component
@@ -258,8 +515,8 @@ mod tests{
     #[test]
     fn test_connected_components_multiple_components(){
         let graph = Graph::from_csvs(
-            "tests\\test_data\\edges_multi_component.csv",
-            "tests\\test_data\\nodes_multi_component.csv"
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
         ).unwrap();
         let (_component, num_components) = graph.connected_components();
         assert_eq!(num_components,3); //There should be 3 components in graph
@@ -268,8 +525,8 @@ mod tests{
     #[test]
     fn test_count_components(){
         let graph = Graph::from_csvs(
-            "tests\\test_data\\edges_single_component.csv", 
-            "tests\\test_data\\nodes_single_component.csv"
+            test_data("edges_single_component.csv"), 
+            test_data("nodes_single_component.csv")
         ).unwrap();
         let (component,num_components) = graph.connected_components();
         let component_sizes = count_components(&component, num_components);
@@ -280,35 +537,913 @@ mod tests{
     #[test]
     fn test_show_aggregation(){
         let graph = Graph::from_csvs(
-            "tests\\test_data\\edges_multi_component.csv",
-            "tests\\test_data\\nodes_multi_component.csv"
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
         ).unwrap();
         
         let (component, num_components) = graph.connected_components();
         let component_scale = get_component_scale(&component, num_components, true);
         let result = show_aggregation(&component_scale,
-            "tests\\test_output\\example_aggregation.png");
+            test_output("example_aggregation.png"));
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_visualize_connectivity(){
         let graph = Graph::from_csvs(
-            "tests\\test_data\\edges_multi_component.csv",
-            "tests\\test_data\\nodes_multi_component.csv"
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
         ).unwrap();
         let result = graph.visualize_connectivity(
-            "tests\\test_output\\example_connectivity.png", 
+            test_output("example_connectivity.png"), 
             1.5, (1024,1024), 
             "Multi-component test example");
         assert!(result.is_ok())
     }
 
+    #[test]
+    fn test_visualize_connectivity_styled(){
+        let graph = Graph::from_csvs(
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
+        ).unwrap();
+        let style = DrawStyle{node_sizing:NodeSizing::InDegree,..DrawStyle::default()};
+        let result = graph.visualize_connectivity_styled(
+            test_output("example_connectivity_styled.png"),
+            1.5, (1024,1024),
+            "Multi-component styled example",
+            &style);
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn test_render_connectivity_sampled(){
+        let graph = Graph::from_csvs(
+            test_data("edges_single_component.csv"),
+            test_data("nodes_single_component.csv")
+        ).unwrap();
+        let (component, num_components) = graph.connected_components();
+        let positions = layout_components(&component, num_components, DRAWING_AREA, 1.5);
+        assert!(positions.iter().all(|position| position.is_some()));
+        let style = DrawStyle{max_edges:Some(2),..DrawStyle::default()};
+        let timings = graph.render_connectivity(&component, num_components, &positions,
+            test_output("example_connectivity_sampled.png"), (512,512),
+            "Sampled edges example", &style).unwrap();
+        assert_eq!(timings.edges_drawn, 2);
+        assert_eq!(timings.edges_skipped, 3);
+
+        //A layout for a different graph is an error rather than a panic
+        let error = graph.render_connectivity(&component, num_components, &positions[1..],
+            test_output("example_connectivity_sampled.png"), (512,512), "Short layout", &style).unwrap_err();
+        assert!(error.to_string().contains("got 5 and 4"));
+    }
+
+    #[test]
+    fn test_write_html(){
+        let graph = Graph::from_csvs(
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
+        ).unwrap();
+        let (component, num_components) = graph.connected_components();
+        let positions = layout_components(&component, num_components, DRAWING_AREA, 1.5);
+        let path = test_output("example_graph.html");
+        write_html(&graph, &component, num_components, &positions, &path, "Multi-component <example>").unwrap();
+        let page = std::fs::read_to_string(&path).unwrap();
+        assert!(page.contains("<title>Multi-component &lt;example&gt;</title>"));
+        assert!(page.contains("\"id\":5,\"label\":\"Paper\",\"subject\":\"Physics\""));
+        assert!(page.contains("\"subjects\":[\"Chemistry\",\"Physics\"]"));
+        //Everything must be inlined so the page works offline
+        assert!(!page.contains("src=\"http"));
+
+        //A title naming a placeholder must not pull the graph data into the heading
+        let path = std::env::temp_dir().join("citation_connectivity_placeholder_title.html");
+        write_html(&graph, &component, num_components, &positions, &path, "__GRAPH_DATA__").unwrap();
+        let page = std::fs::read_to_string(&path).unwrap();
+        assert!(page.contains("<title>__GRAPH_DATA__</title>"));
+        assert_eq!(page.matches("\"subjects\":[").count(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_graph_formats(){
+        let graph = Graph::from_csvs(
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
+        ).unwrap();
+        let (component, _num_components) = graph.connected_components();
+        let centrality = graph.degree_centrality();
+        assert_eq!(centrality, vec![0.5, 0.5, 0.5, 0.5, 0.0]);
+        let options = ExportOptions{
+            include_features:true,
+            components:Some(&component),
+            node_metrics:vec![("degree_centrality", &centrality)],
+            ..ExportOptions::default()
+        };
+
+        write_graphml(&graph, &options, test_output("example_graph.graphml")).unwrap();
+        let graphml = std::fs::read_to_string(test_output("example_graph.graphml")).unwrap();
+        assert!(graphml.contains("<key id=\"d4\" for=\"node\" attr.name=\"degree_centrality\" attr.type=\"double\"/>"));
+        assert!(graphml.contains("<data key=\"d2\">[0,0,0]</data>"));
+        assert!(graphml.contains("<edge source=\"3\" target=\"4\"><data key=\"type\">CITES</data></edge>"));
+
+        write_gexf(&graph, &options, test_output("example_graph.gexf")).unwrap();
+        let gexf = std::fs::read_to_string(test_output("example_graph.gexf")).unwrap();
+        assert!(gexf.contains("<node id=\"4\" label=\"Paper\">"));
+        assert!(gexf.contains("<attvalue for=\"1\" value=\"Chemistry\"/>"));
+        assert_eq!(gexf.matches("<edge ").count(), 4);
+
+        write_dot(&graph, &options, test_output("example_graph.dot")).unwrap();
+        let dot = std::fs::read_to_string(test_output("example_graph.dot")).unwrap();
+        assert!(dot.contains("  5 [\"label\"=\"Paper\", \"subject\"=\"Physics\", \"features\"=\"[0,0,0]\", \"component\"=\"3\", \"degree_centrality\"=\"0\"];"));
+        assert!(dot.contains("  1 -> 2 [type=\"CITES\"];"));
+    }
+
+    #[test]
+    fn test_graphml_gexf_round_trip(){
+        let graph = Graph::from_csvs(
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
+        ).unwrap();
+        let options = ExportOptions{include_features:true,..ExportOptions::default()};
+        write_graphml(&graph, &options, test_output("round_trip.graphml")).unwrap();
+        write_gexf(&graph, &options, test_output("round_trip.gexf")).unwrap();
+        for imported in [
+            Graph::from_graphml(test_output("round_trip.graphml")).unwrap(),
+            Graph::from_gexf(test_output("round_trip.gexf")).unwrap()
+        ]{
+            assert_eq!(imported.n, 5);
+            assert_eq!(imported.calc_num_edges(), 4);
+            assert_eq!(imported.node_data[&3].subject, "Chemistry");
+            assert_eq!(imported.node_data[&3].label, "Paper");
+            assert_eq!(imported.node_data[&3].features, vec![0,0,0]);
+            let (_component, num_components) = imported.connected_components();
+            assert_eq!(num_components, 3);
+        }
+    }
+
+    #[test]
+    fn test_edge_list_import(){
+        let graph = Graph::from_edge_list(test_data("edges_snap.txt"), true).unwrap();
+        assert_eq!(graph.n, 5);
+        assert_eq!(graph.calc_num_edges(), 4);
+        //Metadata is generated for files that carry none
+        assert_eq!(graph.node_data[&40].subject, crate::graph::import::DEFAULT_SUBJECT);
+        assert_eq!(graph.reverse_map[&graph.node_data[&50].mapped_node], 50);
+        let undirected = Graph::from_edge_list(test_data("edges_snap.txt"), false).unwrap();
+        assert_eq!(undirected.calc_num_edges(), 8);
+    }
+
+    #[test]
+    fn test_matrix_market_import(){
+        let graph = Graph::from_matrix_market(test_data("matrix_market.mtx")).unwrap();
+        assert_eq!(graph.n, 4);
+        //Symmetric entries are mirrored and the explicit zero is dropped
+        assert_eq!(graph.calc_num_edges(), 4);
+        let (_component, num_components) = graph.connected_components();
+        assert_eq!(num_components, 2);
+
+        //Size lines are checked before anything is allocated for them, and rows and columns separately
+        assert!(Graph::from_matrix_market(test_data("matrix_market_oversized.mtx")).unwrap_err().contains("more than"));
+        assert!(Graph::from_matrix_market(test_data("matrix_market_out_of_range.mtx")).unwrap_err().contains("\"3 1\""));
+    }
+
+    #[test]
+    fn test_analysis_report(){
+        let graph = Graph::from_csvs(
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
+        ).unwrap();
+        let report = AnalysisReport::from_graph(&graph);
+        assert_eq!(report.nodes, 5);
+        assert_eq!(report.edges, 4);
+        assert_eq!(report.component_sizes, vec![2, 2, 1]);
+        assert_eq!(report.subjects.len(), 2);
+        assert_eq!(report.subjects[0], SubjectStats{
+            subject:String::from("Chemistry"), nodes:2, edges:2, num_components:1, largest_component_share:1.0,
+            triangles:0, transitivity:0.0, average_clustering:0.0
+        });
+
+        report.write_json(test_output("example_report.json")).unwrap();
+        let json = std::fs::read_to_string(test_output("example_report.json")).unwrap();
+        assert!(json.contains("\"component_sizes\": [2, 2, 1]"));
+        assert!(json.contains("\"aggregation\": [0, 0.4, 0.8, 1]"));
+
+        report.write_csv(test_output("example_report.csv")).unwrap();
+        let csv = std::fs::read_to_string(test_output("example_report.csv")).unwrap();
+        assert!(csv.starts_with("scope,metric,rank,value\nall,nodes,,5\n"));
+        assert!(csv.contains("Physics,num_components,,2\n"));
+    }
+
+    #[test]
+    fn test_summarize_components(){
+        let graph = Graph::from_csvs(
+            test_data("edges_single_component.csv"),
+            test_data("nodes_single_component.csv")
+        ).unwrap();
+        let (component, num_components) = graph.connected_components();
+        let summaries = summarize_components(&graph, &component, num_components);
+        assert_eq!(summaries.len(), 1);
+        let summary = &summaries[0];
+        assert_eq!((summary.size, summary.edges), (5, 5));
+        assert!((summary.density - 0.25).abs() < 1e-12);
+        //A 5-cycle seen without direction has every eccentricity equal to 2
+        assert_eq!((summary.diameter, summary.radius, summary.exact_distances), (2, 2, true));
+        assert_eq!(summary.subject_counts, vec![(String::from("Physics"), 4), (String::from("Chemistry"), 1)]);
+        assert_eq!(summary.dominant_subject, "Physics");
+        assert!((summary.subject_entropy - 0.7219280948873623).abs() < 1e-12);
+        assert_eq!((summary.most_cited, summary.most_cited_count), (1, 1));
+    }
+
+    #[test]
+    fn test_summarize_large_component_estimate(){
+        //A citation chain longer than EXACT_ECCENTRICITY_LIMIT, where the double sweep is still exact
+        let edges: Vec<(String,String)> = (0..600).map(|i| (i.to_string(), (i + 1).to_string())).collect();
+        let graph = crate::graph::import::build_graph(Vec::new(), &edges);
+        let (component, num_components) = graph.connected_components();
+        let summary = &summarize_components(&graph, &component, num_components)[0];
+        assert_eq!((summary.size, summary.diameter, summary.radius, summary.exact_distances), (601, 600, 300, false));
+    }
+
+    #[test]
+    fn test_shortest_paths(){
+        let graph = Graph::from_csvs(
+            test_data("edges_single_component.csv"),
+            test_data("nodes_single_component.csv")
+        ).unwrap();
+        assert_eq!(graph.bfs_distances(0, Direction::Directed), vec![Some(0), Some(1), Some(2), Some(3), Some(4)]);
+        assert_eq!(graph.bfs_distances(0, Direction::Undirected), vec![Some(0), Some(1), Some(2), Some(2), Some(1)]);
+        assert_eq!(graph.shortest_path(0, 3, Direction::Directed), Some(vec![0, 1, 2, 3]));
+        assert_eq!(graph.shortest_path(0, 3, Direction::Undirected), Some(vec![0, 4, 3]));
+        assert_eq!(graph.citation_chain(5, 2, Direction::Directed), Ok(Some(vec![5, 1, 2])));
+        assert!(graph.citation_chain(5, 42, Direction::Directed).is_err());
+
+        let multi = Graph::from_csvs(
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
+        ).unwrap();
+        assert_eq!(multi.citation_chain(1, 3, Direction::Undirected), Ok(None));
+    }
+
+    #[test]
+    fn test_sample_distances(){
+        let graph = Graph::from_csvs(
+            test_data("edges_single_component.csv"),
+            test_data("nodes_single_component.csv")
+        ).unwrap();
+        let directed = graph.sample_distances(10, Direction::Directed);
+        assert_eq!(directed.histogram, vec![0, 5, 5, 5, 5]);
+        assert_eq!((directed.sources, directed.reachable_pairs, directed.unreachable_pairs), (5, 20, 0));
+        assert_eq!(directed.average_path_length, 2.5);
+        let undirected = graph.sample_distances(5, Direction::Undirected);
+        assert_eq!(undirected.histogram, vec![0, 10, 10]);
+        assert_eq!((undirected.average_path_length, undirected.max_distance()), (1.5, 2));
+        assert_eq!(graph.sample_distances(2, Direction::Directed).sources, 2);
+    }
+
+    #[test]
+    fn test_topological_sort(){
+        let graph = Graph::from_edge_list(test_data("edges_snap.txt"), true).unwrap();
+        assert!(!graph.is_dag());
+        assert_eq!(graph.topological_sort(), None);
+        let (acyclic, removed) = graph.make_acyclic();
+        assert_eq!(removed.len(), 1);
+        let order = acyclic.topological_sort().unwrap();
+        //Every remaining citation points forward in the order
+        let mut position = vec![0;acyclic.n];
+        for (rank, v) in order.iter().enumerate(){
+            position[*v] = rank;
+        }
+        for (v, neighbors) in acyclic.outedges.iter().enumerate(){
+            assert!(neighbors.iter().all(|w| position[v] < position[*w]));
+        }
+        assert_eq!(acyclic.calc_num_edges(), graph.calc_num_edges() - 1);
+    }
+
+    #[test]
+    fn test_cyclic_components(){
+        let graph = Graph::from_csvs(
+            test_data("edges_single_component.csv"),
+            test_data("nodes_single_component.csv")
+        ).unwrap();
+        let cyclic = graph.cyclic_components();
+        assert_eq!(cyclic.len(), 1);
+        assert_eq!(cyclic[0].members, vec![1, 2, 3, 4, 5]);
+        assert_eq!(cyclic[0].example_cycle, vec![1, 2, 3, 4, 5, 1]);
+
+        let graph = Graph::from_edge_list(test_data("edges_cycles.txt"), true).unwrap();
+        assert_eq!(graph.strongly_connected_components().len(), 4);
+        let cyclic = graph.cyclic_components();
+        assert_eq!(cyclic.len(), 2);
+        assert_eq!(cyclic[0].members, vec![1, 2, 3, 4]);
+        assert_eq!(cyclic[0].example_cycle, vec![1, 2, 3, 1]);
+        assert_eq!(cyclic[1].example_cycle, vec![5, 5]);
+    }
+
+    #[test]
+    fn test_make_acyclic(){
+        //Dropping 2 -> 3 breaks both cycles, and the self-citation always has to go
+        let graph = Graph::from_edge_list(test_data("edges_cycles.txt"), true).unwrap();
+        let (acyclic, mut removed) = graph.make_acyclic();
+        removed.sort_unstable();
+        assert_eq!(removed, vec![(2, 3), (5, 5)]);
+        assert!(acyclic.is_dag());
+        assert_eq!(acyclic.calc_num_edges(), 6);
+        assert_eq!(acyclic.node_data.len(), graph.node_data.len());
+
+        //Every paper cites every other one, so any ordering has to drop exactly one citation of each pair
+        let mut complete = Graph::new();
+        let paper = NodeData{mapped_node:0, label:String::from("Paper"), subject:String::from("Physics"), features:vec![]};
+        for id in 0..30{
+            complete.add_node(id, paper.clone()).unwrap();
+        }
+        for citing in 0..30{
+            for cited in (0..30).filter(|cited| *cited != citing){
+                complete.add_edge(citing, cited).unwrap();
+            }
+        }
+        let (acyclic, removed) = complete.make_acyclic();
+        assert!(acyclic.is_dag());
+        assert_eq!(removed.len(), 30 * 29 / 2);
+        assert!(removed.iter().all(|(citing, cited)| !removed.contains(&(*cited, *citing))));
+    }
+
+    #[test]
+    fn test_search_path_weights(){
+        let graph = Graph::from_edge_list(test_data("edges_main_path.txt"), true).unwrap();
+        let weight_of = |weights:&Vec<Vec<f64>>, citing:usize, cited:usize| {
+            let v = graph.node_data[&citing].mapped_node;
+            let i = graph.outedges[v].iter().position(|w| graph.reverse_map[w] == cited).unwrap();
+            weights[v][i]
+        };
+        let spc = graph.search_path_weights(TraversalWeight::Spc).unwrap();
+        assert_eq!(weight_of(&spc, 2, 4), 2.0);
+        assert_eq!(weight_of(&spc, 3, 5), 2.0);
+        assert_eq!(weight_of(&spc, 5, 7), 3.0);
+        assert_eq!(weight_of(&spc, 6, 7), 1.0);
+        let splc = graph.search_path_weights(TraversalWeight::Splc).unwrap();
+        assert_eq!(weight_of(&splc, 2, 4), 5.0);
+        assert_eq!(weight_of(&splc, 3, 5), 4.0);
+        let spnp = graph.search_path_weights(TraversalWeight::Spnp).unwrap();
+        assert_eq!(weight_of(&spnp, 5, 7), 6.0);
+        assert_eq!(weight_of(&spnp, 2, 4), 5.0);
+
+        let cyclic = Graph::from_edge_list(test_data("edges_snap.txt"), true).unwrap();
+        assert!(cyclic.search_path_weights(TraversalWeight::Spc).is_err());
+    }
+
+    #[test]
+    fn test_main_path(){
+        let graph = Graph::from_edge_list(test_data("edges_main_path.txt"), true).unwrap();
+        //The local search starts on 2 -> 4 and follows the tie at 4 down both branches
+        let local = graph.main_path(TraversalWeight::Spc, MainPathSearch::Local).unwrap();
+        let citations: Vec<(usize,usize)> = local.citations.iter().map(|(v, w, _)| (*v, *w)).collect();
+        assert_eq!(citations, vec![(2, 4), (4, 5), (4, 6), (5, 7), (6, 7)]);
+        assert_eq!(local.total_weight, 8.0);
+        assert_eq!((local.graph.n, local.graph.calc_num_edges()), (5, 5));
+
+        let global = graph.main_path(TraversalWeight::Spc, MainPathSearch::Global).unwrap();
+        let citations: Vec<(usize,usize)> = global.citations.iter().map(|(v, w, _)| (*v, *w)).collect();
+        assert_eq!(citations, vec![(1, 3), (3, 5), (5, 7)]);
+        assert_eq!(global.total_weight, 6.0);
+        let path = &global.graph;
+        assert_eq!(path.reverse_map[&path.outedges[path.node_data[&1].mapped_node][0]], 3);
+        path.visualize_connectivity(test_output("example_main_path.png"), 3.0, (512,512), "Main Path").unwrap();
+    }
+
+    #[test]
+    fn test_bibliographic_coupling(){
+        let graph = Graph::from_edge_list(test_data("edges_coupling.txt"), true).unwrap();
+        let mut links = graph.bibliographic_coupling(1).links();
+        links.sort_unstable();
+        assert_eq!(links, vec![(1, 2, 2), (1, 6, 1)]);
+        let coupling = graph.bibliographic_coupling(2);
+        assert_eq!(coupling.num_links(), 1);
+        //Every paper is kept, so uncoupled papers are singletons
+        let (_component, num_components) = coupling.graph.connected_components();
+        assert_eq!(num_components, 5);
+        coupling.graph.visualize_connectivity(test_output("example_coupling.png"), 3.0, (512,512), "Bibliographic Coupling").unwrap();
+    }
+
+    #[test]
+    fn test_co_citation(){
+        let graph = Graph::from_edge_list(test_data("edges_coupling.txt"), true).unwrap();
+        let mut links = graph.co_citation(1).links();
+        links.sort_unstable();
+        assert_eq!(links, vec![(3, 4, 2), (3, 5, 1), (4, 5, 1)]);
+        let co_citation = graph.co_citation(2);
+        assert_eq!(co_citation.links(), vec![(3, 4, 2)]);
+        let v = co_citation.graph.node_data[&3].mapped_node;
+        assert_eq!(co_citation.weights[v], vec![2]);
+        let path = test_output("example_co_citation.csv");
+        graph.co_citation(1).write_links(&path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written.lines().take(2).collect::<Vec<_>>(), vec!["source,target,weight", "3,4,2"]);
+    }
+
+    #[test]
+    fn test_coreness(){
+        let graph = Graph::from_edge_list(test_data("edges_cycles.txt"), true).unwrap();
+        let by_id = |coreness:Vec<usize>| -> Vec<usize> {
+            (1..=7).map(|id| coreness[graph.node_data[&id].mapped_node]).collect()
+        };
+        assert_eq!(by_id(graph.coreness(CoreDegree::Undirected)), vec![2, 2, 2, 2, 1, 1, 1]);
+        //The self-citation of 5 does not count, so peeling 5 unravels the whole tail
+        assert_eq!(by_id(graph.coreness(CoreDegree::In)), vec![1, 1, 1, 1, 0, 0, 0]);
+        assert_eq!(by_id(graph.coreness(CoreDegree::Out)), vec![1, 1, 1, 1, 0, 0, 0]);
+
+        let core = graph.k_core(2, CoreDegree::Undirected);
+        assert_eq!((core.n, core.calc_num_edges()), (4, 5));
+        assert!(core.node_data.contains_key(&4) && !core.node_data.contains_key(&5));
+
+        //Citing the same paper twice still makes it a single neighbour
+        let mut repeated = Graph::new();
+        let paper = NodeData{mapped_node:0, label:String::from("Paper"), subject:String::from("Physics"), features:vec![]};
+        for id in [1, 2]{
+            repeated.add_node(id, paper.clone()).unwrap();
+        }
+        for (citing, cited) in [(1, 2), (1, 2), (2, 1), (2, 1)]{
+            repeated.add_edge(citing, cited).unwrap();
+        }
+        for degree in [CoreDegree::Undirected, CoreDegree::In, CoreDegree::Out]{
+            assert_eq!(repeated.coreness(degree), vec![1, 1]);
+        }
+    }
+
+    #[test]
+    fn test_core_profile(){
+        let graph = Graph::from_edge_list(test_data("edges_cycles.txt"), true).unwrap();
+        let profile = graph.core_profile(CoreDegree::Undirected);
+        assert_eq!(profile.len(), 3);
+        assert_eq!((profile[1].nodes, profile[1].num_components), (7, 2));
+        assert_eq!(profile[2], CoreLevel{k:2, nodes:4, num_components:1, giant_share:1.0});
+        show_core_profile(&profile, test_output("example_core_profile.png"), "k-core Profile").unwrap();
+    }
+
+    #[test]
+    fn test_clustering(){
+        //Triangles 1-2-3 and 2-3-4 share the 2-3 edge, the 5-6-7 tail has none
+        let graph = Graph::from_edge_list(test_data("edges_cycles.txt"), true).unwrap();
+        let stats = graph.clustering();
+        let by_id = |id:usize| graph.node_data[&id].mapped_node;
+        let triangles: Vec<usize> = (1..=7).map(|id| stats.triangles[by_id(id)]).collect();
+        assert_eq!(triangles, vec![1, 2, 2, 1, 0, 0, 0]);
+        assert_eq!(stats.total_triangles, 2);
+        //6 closed triples out of 1 + 3 + 3 + 1 + 1 = 9 connected ones
+        assert!((stats.transitivity - 6.0 / 9.0).abs() < 1e-12);
+        assert!((stats.local_clustering[by_id(2)] - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(stats.local_clustering[by_id(1)], 1.0);
+        assert!((stats.average_clustering - (1.0 + 2.0 / 3.0 + 2.0 / 3.0 + 1.0) / 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cut_structure(){
+        let graph = Graph::from_edge_list(test_data("edges_cut_points.txt"), true).unwrap();
+        let cuts = graph.cut_structure();
+        let mut points = cuts.articulation_points.clone();
+        points.sort_by_key(|cut| cut.node);
+        assert_eq!(points, vec![
+            CutVertex{node:1, piece_sizes:vec![6, 1]},
+            CutVertex{node:3, piece_sizes:vec![4, 3]},
+            CutVertex{node:4, piece_sizes:vec![4, 3]},
+            CutVertex{node:5, piece_sizes:vec![5, 2]}
+        ]);
+        assert_eq!(cuts.articulation_points[0].papers_separated(), 3);
+        assert_eq!(cuts.articulation_points.last().unwrap().node, 1);
+        let mut bridges: Vec<(usize,usize)> = cuts.bridges.iter().map(|(v, w)| (*v.min(w), *v.max(w))).collect();
+        bridges.sort_unstable();
+        assert_eq!(bridges, vec![(1, 8), (3, 4), (4, 5)]);
+
+        //A cycle has no single point of failure
+        let cycle = Graph::from_csvs(
+            test_data("edges_single_component.csv"),
+            test_data("nodes_single_component.csv")
+        ).unwrap();
+        assert_eq!(cycle.cut_structure(), CutStructure{articulation_points:vec![], bridges:vec![]});
+    }
+
+    #[test]
+    fn test_visualize_highlighted(){
+        let graph = Graph::from_edge_list(test_data("edges_cut_points.txt"), true).unwrap();
+        let cuts = graph.cut_structure();
+        let mapped = |id:&usize| graph.node_data[id].mapped_node;
+        let style = DrawStyle{
+            highlight_nodes:cuts.articulation_points.iter().map(|cut| mapped(&cut.node)).collect(),
+            highlight_edges:cuts.bridges.iter().map(|(v, w)| (mapped(v), mapped(w))).collect(),
+            ..DrawStyle::default()
+        };
+        graph.visualize_connectivity_styled(test_output("example_cut_points.png"), 3.0, (512,512), "Cut Points", &style).unwrap();
+    }
+
+    #[test]
+    fn test_disjoint_set(){
+        let mut sets = DisjointSet::new(5);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(3, 4));
+        assert!(!sets.union(1, 0));
+        assert!(sets.union(1, 4));
+        assert_eq!(sets.num_sets(), 2);
+        assert_eq!(sets.set_size(3), 4);
+        assert_eq!(sets.find(0), sets.find(4));
+        assert_ne!(sets.find(2), sets.find(0));
+    }
+
+    #[test]
+    fn test_node_robustness(){
+        let graph = Graph::from_edge_list(test_data("edges_cut_points.txt"), true).unwrap();
+        let (component, num_components) = graph.connected_components();
+        let scale = get_component_scale(&component, num_components, true);
+        let by_degree = graph.node_robustness(&RemovalStrategy::Degree);
+        assert_eq!(by_degree.len(), graph.n + 1);
+        assert_eq!((by_degree[0].giant_share, by_degree[0].num_components), (scale[1], num_components));
+        assert_eq!(by_degree.last().unwrap().giant_share, 0.0);
+
+        //Knocking out paper 4 first splits the two triangles apart
+        let mut scores = vec![0.0;graph.n];
+        scores[graph.node_data[&4].mapped_node] = 1.0;
+        let ranked = graph.node_robustness(&RemovalStrategy::Ranking(scores));
+        assert_eq!(ranked[1], RobustnessPoint{removed:1, fraction_removed:0.125, num_components:2, giant_share:0.5});
+
+        let random = graph.node_robustness(&RemovalStrategy::Random);
+        assert!(random.windows(2).all(|pair| pair[1].giant_share <= pair[0].giant_share));
+        show_robustness(&[("Degree", &by_degree), ("Random", &random)], test_output("example_robustness.png")).unwrap();
+    }
+
+    #[test]
+    fn test_edge_robustness(){
+        let graph = Graph::from_edge_list(test_data("edges_cut_points.txt"), true).unwrap();
+        let points = graph.edge_robustness();
+        assert_eq!(points.len(), graph.calc_num_edges() + 1);
+        assert_eq!((points[0].num_components, points[0].giant_share), (1, 1.0));
+        assert_eq!(*points.last().unwrap(), RobustnessPoint{removed:9, fraction_removed:1.0, num_components:8, giant_share:0.125});
+    }
+
+    #[test]
+    fn test_incremental_connectivity(){
+        let graph = Graph::from_csvs(
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
+        ).unwrap();
+        let mut connectivity = IncrementalConnectivity::from_graph(&graph);
+        let (component, num_components) = graph.connected_components();
+        let mut sizes = count_components(&component, num_components);
+        sizes.sort_by_key(|size| std::cmp::Reverse(*size));
+        assert_eq!(connectivity.component_sizes(), sizes);
+        assert_eq!(connectivity.num_components(), num_components);
+        assert!(connectivity.same_component(1, 2));
+        assert!(!connectivity.same_component(2, 3));
+
+        //New citations merge components and bring in unseen papers
+        assert!(connectivity.add_edge(5, 3));
+        assert!(!connectivity.add_edge(4, 5));
+        assert!(connectivity.add_edge(6, 1));
+        assert_eq!(connectivity.component_sizes(), vec![3, 3]);
+        assert_eq!((connectivity.num_nodes(), connectivity.largest_component_size()), (6, 3));
+        assert_eq!(connectivity.component_size(6), Some(3));
+        assert_eq!(connectivity.component_size(7), None);
+        assert!(!connectivity.same_component(6, 7));
+        assert!(connectivity.add_edge(2, 4));
+        assert_eq!((connectivity.num_components(), connectivity.largest_component_share()), (1, 1.0));
+    }
+
+    #[test]
+    fn test_edge_feed(){
+        let feed: Vec<(usize,usize)> = edge_feed(test_data("edges_snap.txt")).unwrap().collect::<Result<_,_>>().unwrap();
+        assert_eq!(feed, vec![(10, 20), (20, 30), (30, 10), (40, 50)]);
+        let mut connectivity = IncrementalConnectivity::new();
+        for (citing, cited) in feed{
+            connectivity.add_edge(citing, cited);
+        }
+        assert_eq!(connectivity.component_sizes(), vec![3, 2]);
+        assert!(edge_feed(test_data("missing.txt")).is_err());
+    }
+
+    #[test]
+    fn test_binary_round_trip(){
+        let mut graph = Graph::from_csvs(test_data("edges_multi_component.csv"), test_data("nodes_multi_component.csv")).unwrap();
+        graph.save_binary(test_output("example_graph.ccg"), &[], CacheValidation::ModifiedTime).unwrap();
+        assert_eq!(Graph::load_binary(test_output("example_graph.ccg")).unwrap(), graph);
+        //Features that don't fit in one bit are stored as plain bytes
+        graph.node_data.get_mut(&1).unwrap().features = vec![0, 3, 1, 200];
+        let path = std::env::temp_dir().join("citation_connectivity_raw_features.ccg");
+        graph.save_binary(&path, &[], CacheValidation::ModifiedTime).unwrap();
+        assert_eq!(Graph::load_binary(&path).unwrap(), graph);
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(Graph::load_binary(&path).unwrap_err().contains("checksum"));
+        bytes[8] = 99;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(Graph::load_binary(&path).unwrap_err().contains("version"));
+        assert!(Graph::load_binary(test_data("edges_snap.txt")).is_err());
+
+        //Two mapped nodes claiming the same paper would corrupt reverse_map
+        let paper = graph.node_data[&1].clone();
+        let duplicated = Graph{n:2, outedges:vec![vec![], vec![]], node_data:HashMap::from([(1, paper)]), reverse_map:HashMap::from([(0, 1), (1, 1)])};
+        duplicated.save_binary(&path, &[], CacheValidation::ModifiedTime).unwrap();
+        assert!(Graph::load_binary(&path).unwrap_err().contains("node id 1 appears twice"));
+    }
+
+    #[test]
+    fn test_cache_invalidation(){
+        let dir = std::env::temp_dir().join("citation_connectivity_cache_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (edges, nodes, cache) = (dir.join("edges.csv"), dir.join("nodes.csv"), dir.join("graph.ccg"));
+        std::fs::copy(test_data("edges_multi_component.csv"), &edges).unwrap();
+        std::fs::copy(test_data("nodes_multi_component.csv"), &nodes).unwrap();
+        let _ = std::fs::remove_file(&cache);
+
+        let graph = Graph::from_csvs_cached(&edges, &nodes, &cache, CacheValidation::ModifiedTime).unwrap();
+        assert_eq!(graph, Graph::from_csvs(&edges, &nodes).unwrap());
+        assert_eq!(Graph::load_binary_if_fresh(&cache, &[&edges, &nodes], CacheValidation::ModifiedTime).unwrap(), Some(Graph::from_csvs(&edges, &nodes).unwrap()));
+        //The sources were not hashed, so the cache can't vouch for their contents
+        assert_eq!(Graph::load_binary_if_fresh(&cache, &[&edges, &nodes], CacheValidation::ContentHash).unwrap(), None);
+        //The same files under another spelling of their paths
+        let (edges_alias, nodes_alias) = (dir.join(".").join("edges.csv"), dir.join("..").join("citation_connectivity_cache_test").join("nodes.csv"));
+        assert!(Graph::load_binary_if_fresh(&cache, &[&edges_alias, &nodes_alias], CacheValidation::ModifiedTime).unwrap().is_some());
+        //Built from different sources
+        assert_eq!(Graph::load_binary_if_fresh(&cache, &[&edges], CacheValidation::ModifiedTime).unwrap(), None);
+
+        let mut contents = std::fs::read_to_string(&edges).unwrap();
+        let index = contents.lines().count() - 1;
+        contents.push_str(&format!("\n{},1,5,related_to", index));
+        std::fs::write(&edges, contents).unwrap();
+        for validation in [CacheValidation::ModifiedTime, CacheValidation::ContentHash]{
+            assert_eq!(Graph::load_binary_if_fresh(&cache, &[&edges, &nodes], validation).unwrap(), None);
+        }
+        let rebuilt = Graph::from_csvs_cached(&edges, &nodes, &cache, CacheValidation::ContentHash).unwrap();
+        assert_eq!(rebuilt.outedges.iter().map(|neighbors| neighbors.len()).sum::<usize>(), graph.outedges.iter().map(|neighbors| neighbors.len()).sum::<usize>() + 1);
+        for validation in [CacheValidation::ModifiedTime, CacheValidation::ContentHash]{
+            assert!(Graph::load_binary_if_fresh(&cache, &[&edges, &nodes], validation).unwrap().is_some());
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_components_match_sequential(){
+        let graphs = [
+            Graph::from_csvs(test_data("edges_multi_component.csv"), test_data("nodes_multi_component.csv")).unwrap(),
+            Graph::from_edge_list(test_data("edges_cut_points.txt"), true).unwrap(),
+            Graph::from_edge_list(test_data("edges_snap.txt"), true).unwrap(),
+            //A long path numbered backwards needs many rounds of propagation
+            crate::graph::import::build_graph(Vec::new(), &(0..200).map(|i| ((200 - i).to_string(), (199 - i).to_string())).collect::<Vec<_>>()),
+            //Sparse random graph large enough for connected_components to take the parallel path
+            crate::graph::import::build_graph(Vec::new(), &(0..12_000).map(|i| {
+                let target = (i * 7919 + 13) % 15_000;
+                (i.to_string(), target.to_string())
+            }).collect::<Vec<_>>())
+        ];
+        for graph in graphs.iter(){
+            assert_eq!(graph.connected_components_parallel(), graph.connected_components_sequential());
+        }
+        assert_eq!(graphs[4].connected_components(), graphs[4].connected_components_sequential());
+        let report = AnalysisReport::from_graph(&graphs[0]);
+        assert_eq!(report.subjects.iter().map(|stats| stats.num_components).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_relabel_by_size(){
+        let component = vec![Some(1), Some(2), Some(2), Some(3), Some(2), Some(3)];
+        assert_eq!(relabel_by_size(&component, 3), vec![Some(3), Some(1), Some(1), Some(2), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_write_component_assignments(){
+        //Node 5 cites nobody, so it sits in a singleton that must be renumbered last
+        let graph = Graph::from_csvs(
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
+        ).unwrap();
+        let (component, num_components) = graph.connected_components();
+        let path = test_output("example_assignments.csv");
+        write_component_assignments(&graph, &component, num_components, &path).unwrap();
+        let rows = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(rows, "node_id,component_id,component_size,subject\n\
+            1,1,2,Physics\n2,1,2,Physics\n3,2,2,Chemistry\n4,2,2,Chemistry\n5,3,1,Physics\n");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip(){
+        let graph = Graph::from_csvs(test_data("edges_multi_component.csv"), test_data("nodes_multi_component.csv")).unwrap();
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(serde_json::from_str::<Graph>(&json).unwrap(), graph);
+
+        let (component, num_components) = graph.connected_components();
+        let assignments = component_assignments(&graph, &component, num_components);
+        assert_eq!(serde_json::to_value(&assignments[4]).unwrap(), serde_json::json!({"node":5,"component":3,"component_size":1,"subject":"Physics"}));
+        let summaries = summarize_components(&graph, &component, num_components);
+        let json = serde_json::to_string(&summaries).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<ComponentSummary>>(&json).unwrap(), summaries);
+        let report = AnalysisReport::from_graph(&graph);
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<AnalysisReport>(&json).unwrap(), report);
+    }
+
+    #[test]
+    fn test_mutation(){
+        let mut graph = Graph::from_csvs(test_data("edges_multi_component.csv"), test_data("nodes_multi_component.csv")).unwrap();
+        let paper = |subject:&str| NodeData{mapped_node:0, label:String::from("Paper"), subject:String::from(subject), features:vec![0, 1]};
+        assert_eq!(graph.add_node(9, paper("Biology")), Ok(5));
+        assert!(graph.add_node(9, paper("Biology")).is_err());
+        graph.add_edge(9, 5).unwrap();
+        assert!(graph.add_edge(9, 42).is_err());
+        assert!(graph.contains_edge(9, 5) && !graph.contains_edge(5, 9));
+        assert_eq!(graph.connected_components().1, 3);
+        graph.validate().unwrap();
+
+        //Removing paper 2 renumbers the papers after it and drops the citation 1 -> 2
+        let removed = graph.remove_node(2).unwrap();
+        assert_eq!(removed.mapped_node, 1);
+        assert_eq!(graph.n, 5);
+        assert_eq!(graph.mapped_node(9), Some(4));
+        assert_eq!(graph.outedges[graph.mapped_node(1).unwrap()], Vec::<usize>::new());
+        assert!(graph.contains_edge(9, 5) && graph.contains_edge(3, 4));
+        assert_eq!(graph.remove_node(2), None);
+        graph.validate().unwrap();
+
+        assert!(graph.remove_edge(9, 5));
+        assert!(!graph.remove_edge(9, 5));
+        assert_eq!(graph.calc_num_edges(), 2);
+        graph.validate().unwrap();
+
+        //Paper 2 and its citations come back as a new mapped node. Like add_edge, merge keeps repeated
+        //citations, so 3 <-> 4 is now there twice until the graph is cleaned.
+        let other = Graph::from_csvs(test_data("edges_multi_component.csv"), test_data("nodes_multi_component.csv")).unwrap();
+        graph.merge(&other);
+        graph.validate().unwrap();
+        assert_eq!(graph.n(), 6);
+        assert_eq!(graph.mapped_node(2), Some(5));
+        assert_eq!(graph.calc_num_edges(), 6);
+        assert!(graph.contains_edge(1, 2));
+        let report = graph.clean(&CleaningPolicy{dedupe:true, ..CleaningPolicy::default()});
+        assert_eq!((report.duplicate_edges.count, graph.calc_num_edges()), (2, 4));
+
+        let broken = Graph{n:1, outedges:vec![], node_data:HashMap::new(), reverse_map:HashMap::new()};
+        assert!(broken.validate().is_err());
+        let mut empty = Graph::new();
+        empty.merge(&other);
+        assert_eq!(empty.connected_components().1, 3);
+    }
+
+    #[test]
+    fn test_cleaning_policies(){
+        let (edges, nodes) = (test_data("edges_dirty.csv"), test_data("nodes_dirty.csv"));
+        assert_eq!(Graph::from_csvs(&edges, &nodes).unwrap_err(), "Target Node ID 9 not found in node data");
+
+        let policy = CleaningPolicy{dedupe:true, drop_self_loops:true, missing_endpoints:MissingEndpoints::Drop};
+        let (graph, report) = Graph::from_csvs_cleaned(&edges, &nodes, &policy).unwrap();
+        graph.validate().unwrap();
+        assert_eq!((graph.n, graph.calc_num_edges()), (4, 2));
+        //The first row for paper 2 wins
+        assert_eq!(graph.node_data[&2].subject, "Physics");
+        assert_eq!((report.nodes_read, report.edges_read, report.edges_kept), (5, 9, 2));
+        assert_eq!(report.duplicate_nodes, Issue{count:1, examples:vec![2]});
+        assert_eq!(report.duplicate_edges, Issue{count:2, examples:vec![(1, 2), (1, 2)]});
+        assert_eq!(report.self_loops, Issue{count:3, examples:vec![(2, 2), (4, 4), (4, 4)]});
+        assert_eq!(report.missing_endpoints, Issue{count:2, examples:vec![(3, 9), (8, 1)]});
+        assert_eq!(report.missing_nodes, vec![8, 9]);
+        assert!(report.dangling_edges.is_empty() && !report.is_clean());
+        let path = test_output("example_quality_report.json");
+        report.write_json(&path).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("\"missing_endpoints\": {\"count\": 2, \"examples\": [[3, 9], [8, 1]]}"));
+
+        //Only dangling citations are left out, and each of them is listed
+        let policy = CleaningPolicy{missing_endpoints:MissingEndpoints::Collect, ..CleaningPolicy::default()};
+        let (mut graph, report) = Graph::from_csvs_cleaned(&edges, &nodes, &policy).unwrap();
+        assert_eq!(graph.calc_num_edges(), 7);
+        assert_eq!(report.duplicate_edges.count, 3);
+        assert_eq!(report.dangling_edges, vec![(3, 9), (8, 1)]);
+
+        let report = graph.clean(&CleaningPolicy{dedupe:true, drop_self_loops:true, ..CleaningPolicy::default()});
+        assert_eq!((report.edges_read, report.edges_kept, report.duplicate_edges.count, report.self_loops.count), (7, 2, 2, 3));
+        assert_eq!(graph.calc_num_edges(), 2);
+        assert!(graph.clean(&CleaningPolicy::default()).is_clean());
+
+        let policy = CleaningPolicy{dedupe:true, missing_endpoints:MissingEndpoints::Create, ..CleaningPolicy::default()};
+        let (graph, report) = Graph::from_csvs_cleaned(&edges, &nodes, &policy).unwrap();
+        graph.validate().unwrap();
+        assert_eq!((graph.n, graph.calc_num_edges()), (6, 6));
+        assert_eq!(graph.node_data[&9].subject, "Unknown");
+        assert!(graph.contains_edge(8, 1) && graph.contains_edge(3, 9));
+        assert_eq!(report.missing_nodes, vec![8, 9]);
+
+        //A missing paper citing itself is created once
+        let (edges, nodes) = (test_data("edges_missing_self_citation.csv"), test_data("nodes_single_paper.csv"));
+        let (graph, report) = Graph::from_csvs_cleaned(&edges, &nodes, &policy).unwrap();
+        graph.validate().unwrap();
+        assert_eq!((graph.n, graph.calc_num_edges()), (2, 2));
+        assert!(graph.contains_edge(7, 7) && graph.contains_edge(1, 7));
+        assert_eq!(report.missing_nodes, vec![7]);
+
+        //Malformed rows are reported with their line instead of panicking
+        let nodes = test_data("nodes_malformed.csv");
+        assert_eq!(Graph::from_csvs(test_data("edges_malformed.csv"), test_data("nodes_multi_component.csv")).unwrap_err(),
+            format!("Line 3 of {} does not contain two numeric node ids", test_data("edges_malformed.csv").display()));
+        assert_eq!(Graph::from_csvs(test_data("edges_multi_component.csv"), &nodes).unwrap_err(),
+            format!("Line 3 of {} does not contain a numeric node id, label, subject and features", nodes.display()));
+    }
+
+    #[test]
+    fn test_streaming_load(){
+        let (edges, nodes) = (test_data("edges_multi_component.csv"), test_data("nodes_multi_component.csv"));
+        let mut passes = Vec::new();
+        let (graph, report) = Graph::from_csvs_streaming(&edges, &nodes, &CleaningPolicy::default(), |progress| passes.push((progress.pass, progress.edges, progress.fraction()))).unwrap();
+        assert_eq!(graph, Graph::from_csvs(&edges, &nodes).unwrap());
+        assert!(report.is_clean());
+        assert_eq!(passes, vec![(1, 4, 1.0), (2, 4, 1.0)]);
+
+        //Same graph and counts as the in-memory cleaning pass
+        let (edges, nodes) = (test_data("edges_dirty.csv"), test_data("nodes_dirty.csv"));
+        assert_eq!(Graph::from_csvs_streaming(&edges, &nodes, &CleaningPolicy::default(), |_| {}).unwrap_err(), "Target Node ID 9 not found in node data");
+        for missing_endpoints in [MissingEndpoints::Drop, MissingEndpoints::Collect, MissingEndpoints::Create]{
+            for (dedupe, drop_self_loops) in [(false, false), (true, false), (true, true)]{
+                let policy = CleaningPolicy{dedupe, drop_self_loops, missing_endpoints};
+                let (streamed, streamed_report) = Graph::from_csvs_streaming(&edges, &nodes, &policy, |_| {}).unwrap();
+                let (graph, report) = Graph::from_csvs_cleaned(&edges, &nodes, &policy).unwrap();
+                assert_eq!(streamed, graph);
+                assert_eq!(streamed_report.duplicate_edges.count, report.duplicate_edges.count);
+                assert_eq!(DataQualityReport{duplicate_edges:report.duplicate_edges.clone(), ..streamed_report}, report);
+            }
+        }
+        assert!(open_input(test_data("missing.csv")).is_err());
+
+        //A missing paper citing itself is created once, as in from_csvs_cleaned
+        let (edges, nodes) = (test_data("edges_missing_self_citation.csv"), test_data("nodes_single_paper.csv"));
+        let policy = CleaningPolicy{missing_endpoints:MissingEndpoints::Create, ..CleaningPolicy::default()};
+        let (streamed, _) = Graph::from_csvs_streaming(&edges, &nodes, &policy, |_| {}).unwrap();
+        assert_eq!(streamed, Graph::from_csvs_cleaned(&edges, &nodes, &policy).unwrap().0);
+
+        //A citation appended between the passes has no slot in the CSR arrays
+        let path = std::env::temp_dir().join("citation_connectivity_growing_edges.csv");
+        std::fs::copy(test_data("edges_multi_component.csv"), &path).unwrap();
+        let error = Graph::from_csvs_streaming(&path, test_data("nodes_multi_component.csv"), &CleaningPolicy::default(), |progress| {
+            if progress.pass == 1{
+                let contents = std::fs::read_to_string(&path).unwrap();
+                std::fs::write(&path, format!("{}\n4,1,2,related_to\n", contents.trim_end())).unwrap();
+            }
+        }).unwrap_err();
+        assert_eq!(error, format!("{} changed between the two passes", path.display()));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(all(feature = "gzip", feature = "zstd"))]
+    #[test]
+    fn test_compressed_input(){
+        use std::io::Write;
+        let dir = std::env::temp_dir().join("citation_connectivity_compressed_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let edges = std::fs::read(test_data("edges_multi_component.csv")).unwrap();
+        let nodes = std::fs::read(test_data("nodes_multi_component.csv")).unwrap();
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&edges).unwrap();
+        std::fs::write(dir.join("edges.csv.gz"), gzip.finish().unwrap()).unwrap();
+        std::fs::write(dir.join("nodes.csv.zst"), zstd::encode_all(&nodes[..], 0).unwrap()).unwrap();
+
+        let expected = Graph::from_csvs(test_data("edges_multi_component.csv"), test_data("nodes_multi_component.csv")).unwrap();
+        assert_eq!(Graph::from_csvs(dir.join("edges.csv.gz"), dir.join("nodes.csv.zst")).unwrap(), expected);
+        let mut last = None;
+        let (graph, _) = Graph::from_csvs_streaming(dir.join("edges.csv.gz"), dir.join("nodes.csv.zst"), &CleaningPolicy::default(), |progress| last = Some(*progress)).unwrap();
+        assert_eq!(graph, expected);
+        let last = last.unwrap();
+        assert_eq!((last.pass, last.edges, last.fraction()), (2, 4, 1.0));
+        //Progress counts the compressed bytes
+        assert!(last.total_bytes < edges.len() as u64 + 32);
+    }
+
+    #[test]
+    fn test_json_string(){
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(json_string("</script>"), "\"\\u003c/script>\"");
+    }
+
+    #[test]
+    fn test_node_radii(){
+        let graph = Graph::from_csvs(
+            test_data("edges_single_component.csv"),
+            test_data("nodes_single_component.csv")
+        ).unwrap();
+        //Every node in the 5-cycle is cited exactly once
+        assert_eq!(node_radii(&graph, &NodeSizing::InDegree, (3,12)), vec![12;5]);
+        let metric = NodeSizing::Metric(vec![0.0, 1.0, 4.0, 0.0, 0.0]);
+        assert_eq!(node_radii(&graph, &metric, (2,10)), vec![2, 6, 10, 2, 2]);
+    }
+
+    #[test]
+    fn test_arrow_head(){
+        let tip = shorten_segment((0,0), (100,0), 10.0);
+        assert_eq!(tip, (90,0));
+        assert_eq!(arrow_head((0,0), tip, 8.0), Some([(90,0), (82,4), (82,-4)]));
+        assert_eq!(arrow_head((5,5), (5,5), 8.0), None);
+    }
+
     #[test]
     fn test_subgraphs(){
         let graph = Graph::from_csvs(
-            "tests\\test_data\\edges_multi_component.csv",
-            "tests\\test_data\\nodes_multi_component.csv"
+            test_data("edges_multi_component.csv"),
+            test_data("nodes_multi_component.csv")
         ).unwrap();
 
         let subgraphs = graph.calculate_subgraphs();
@@ -0,0 +1,128 @@
+use crate::graph::*;
+
+//Safe edits of a Graph by original paper ids. Every method keeps n, outedges, node_data and reverse_map in step:
+//mapped nodes stay 0..n and node_data[id].mapped_node always points back at id through reverse_map.
+impl Graph{
+    //A graph with no papers, to be filled through add_node and add_edge
+    pub fn new() -> Self{
+        Graph{n:0, outedges:Vec::new(), node_data:HashMap::new(), reverse_map:HashMap::new()}
+    }
+
+    //Mapped node of a paper, None if it is not in the graph
+    pub fn mapped_node(&self, id:usize) -> Option<Vertex>{
+        self.node_data.get(&id).map(|data| data.mapped_node)
+    }
+
+    pub fn contains_node(&self, id:usize) -> bool{
+        self.node_data.contains_key(&id)
+    }
+
+    //Whether `citing` cites `cited`
+    pub fn contains_edge(&self, citing:usize, cited:usize) -> bool{
+        match (self.mapped_node(citing), self.mapped_node(cited)){
+            (Some(v), Some(w)) => self.outedges[v].contains(&w),
+            _ => false
+        }
+    }
+
+    //Add a paper under original id `id` and return its mapped node. The mapped_node in `data` is ignored.
+    pub fn add_node(&mut self, id:usize, mut data:NodeData) -> Result<Vertex,String>{
+        if self.contains_node(id){
+            return Err(format!("Node ID {} is already in the graph", id));
+        }
+        let v = self.n;
+        data.mapped_node = v;
+        self.node_data.insert(id, data);
+        self.reverse_map.insert(v, id);
+        self.outedges.push(Vec::new());
+        self.n += 1;
+        Ok(v)
+    }
+
+    //Remove a paper and every citation made or received by it, returning its data. Later mapped nodes
+    //move down by one so the numbering stays contiguous and in the same order.
+    pub fn remove_node(&mut self, id:usize) -> Option<NodeData>{
+        let removed = self.mapped_node(id)?;
+        self.outedges.remove(removed);
+        for neighbors in self.outedges.iter_mut(){
+            neighbors.retain(|w| *w != removed);
+            for w in neighbors.iter_mut().filter(|w| **w > removed){
+                *w -= 1;
+            }
+        }
+        for v in removed + 1..self.n{
+            let original = self.reverse_map.remove(&v).unwrap();
+            self.reverse_map.insert(v - 1, original);
+            self.node_data.get_mut(&original).unwrap().mapped_node = v - 1;
+        }
+        self.reverse_map.remove(&(self.n - 1));
+        self.n -= 1;
+        self.node_data.remove(&id)
+    }
+
+    //Add the citation `citing` -> `cited`; both papers must already be in the graph. Like from_csvs, a citation
+    //that is already present is added again.
+    pub fn add_edge(&mut self, citing:usize, cited:usize) -> Result<(),String>{
+        let v = self.mapped_node(citing).ok_or_else(|| format!("Node ID {} not found in node data", citing))?;
+        let w = self.mapped_node(cited).ok_or_else(|| format!("Target Node ID {} not found in node data", cited))?;
+        self.outedges[v].push(w);
+        Ok(())
+    }
+
+    //Remove every copy of the citation `citing` -> `cited`, returning whether there was one
+    pub fn remove_edge(&mut self, citing:usize, cited:usize) -> bool{
+        let (v, w) = match (self.mapped_node(citing), self.mapped_node(cited)){
+            (Some(v), Some(w)) => (v, w),
+            _ => return false
+        };
+        let before = self.outedges[v].len();
+        self.outedges[v].retain(|u| *u != w);
+        self.outedges[v].len() != before
+    }
+
+    //Add the papers and citations of `other`. Papers are matched by original id: new ones are appended in
+    //other's mapped order, and for papers in both graphs the data already here is kept. Every citation of
+    //`other` is added as add_edge would, so one present in both graphs ends up twice; clean with
+    //CleaningPolicy::dedupe to collapse repeated citations.
+    pub fn merge(&mut self, other:&Graph){
+        for v in 0..other.n{
+            let id = other.reverse_map[&v];
+            if !self.contains_node(id){
+                self.add_node(id, other.node_data[&id].clone()).unwrap();
+            }
+        }
+        for (v, neighbors) in other.outedges.iter().enumerate(){
+            let citing = self.mapped_node(other.reverse_map[&v]).unwrap();
+            for w in neighbors.iter(){
+                let cited = self.mapped_node(other.reverse_map[w]).unwrap();
+                self.outedges[citing].push(cited);
+            }
+        }
+    }
+
+    //Check the invariants between n, outedges, node_data and reverse_map, e.g. on a graph deserialized with the serde feature
+    pub fn validate(&self) -> Result<(),String>{
+        if self.outedges.len() != self.n || self.node_data.len() != self.n || self.reverse_map.len() != self.n{
+            return Err(format!("n is {} but there are {} adjacency lists, {} node data entries and {} reverse map entries",
+                self.n, self.outedges.len(), self.node_data.len(), self.reverse_map.len()));
+        }
+        for v in 0..self.n{
+            let id = self.reverse_map.get(&v).ok_or_else(|| format!("Mapped node {} has no original id", v))?;
+            match self.node_data.get(id){
+                Some(data) if data.mapped_node == v => {},
+                Some(data) => return Err(format!("Node ID {} maps to {} but reverse_map says {}", id, data.mapped_node, v)),
+                None => return Err(format!("Node ID {} has no node data", id))
+            }
+            if let Some(w) = self.outedges[v].iter().find(|w| **w >= self.n){
+                return Err(format!("Mapped node {} cites {} but there are only {} nodes", v, w, self.n));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Graph{
+    fn default() -> Self{
+        Graph::new()
+    }
+}
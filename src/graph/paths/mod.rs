@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+use rand::seq::index::sample;
+use crate::graph::*;
+use crate::graph::component_functions::bfs_distances_within;
+
+//Whether a path has to follow citations from the citing paper to the cited one, or may use them either way
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction{
+    Directed,
+    Undirected
+}
+
+//Distances seen from a set of BFS sources, used to estimate the average path length
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DistanceSample{
+    pub sources:usize,
+    //Ordered (source, target) pairs with a path between them, source != target
+    pub reachable_pairs:usize,
+    pub unreachable_pairs:usize,
+    //Mean distance over the reachable pairs (0 when there are none)
+    pub average_path_length:f64,
+    //histogram[d] is the number of reachable pairs at distance d
+    pub histogram:Vec<usize>
+}
+impl DistanceSample{
+    pub fn max_distance(&self) -> usize{
+        self.histogram.len().saturating_sub(1)
+    }
+}
+
+impl Graph{
+    fn adjacency(&self, direction:Direction) -> Cow<'_, AdjacencyList>{
+        match direction{
+            Direction::Directed => Cow::Borrowed(&self.outedges),
+            Direction::Undirected => Cow::Owned(self.undirected_adjacency())
+        }
+    }
+
+    //Number of citation steps from `source` to every mapped node, None where it cannot be reached
+    pub fn bfs_distances(&self, source:Vertex, direction:Direction) -> Vec<Option<usize>>{
+        bfs_distances_within(&self.adjacency(direction), source, |_| true)
+    }
+
+    //Mapped nodes on a shortest path from `from` to `to` (both included), None if `to` is unreachable
+    pub fn shortest_path(&self, from:Vertex, to:Vertex, direction:Direction) -> Option<Vec<Vertex>>{
+        let adj_list = self.adjacency(direction);
+        let mut parent: Vec<Option<Vertex>> = vec![None;self.n];
+        let mut visited = vec![false;self.n];
+        visited[from] = true;
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(v) = queue.pop_front(){
+            if v == to{
+                break;
+            }
+            for u in adj_list[v].iter(){
+                if !visited[*u]{
+                    visited[*u] = true;
+                    parent[*u] = Some(v);
+                    queue.push_back(*u);
+                }
+            }
+        }
+        if !visited[to]{
+            return None;
+        }
+        //Walk the parents back from the target
+        let mut path = vec![to];
+        while let Some(previous) = parent[*path.last().unwrap()]{
+            path.push(previous);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    //Shortest chain of citations between two papers given by their original ids, as original ids
+    pub fn citation_chain(&self, from_id:usize, to_id:usize, direction:Direction) -> Result<Option<Vec<usize>>,String>{
+        let mapped = |id:usize| self.node_data.get(&id)
+            .map(|data| data.mapped_node)
+            .ok_or_else(|| format!("Node ID {} not found in node data", id));
+        let path = self.shortest_path(mapped(from_id)?, mapped(to_id)?, direction);
+        Ok(path.map(|path| path.iter().map(|v| self.reverse_map[v]).collect()))
+    }
+
+    //Run a BFS from `samples` random sources (every node when samples >= n) and summarize the distances found.
+    //With every node as a source this is the exact all-pairs distribution.
+    pub fn sample_distances(&self, samples:usize, direction:Direction) -> DistanceSample{
+        let adj_list = self.adjacency(direction);
+        let sources: Vec<Vertex> = if samples >= self.n{
+            (0..self.n).collect()
+        }
+        else{
+            sample(&mut rand::thread_rng(), self.n, samples).into_vec()
+        };
+        let mut histogram: Vec<usize> = Vec::new();
+        let mut reachable_pairs = 0;
+        let mut total_distance = 0;
+        for source in sources.iter(){
+            for distance in bfs_distances_within(&adj_list, *source, |_| true).into_iter().flatten(){
+                if distance == 0{
+                    continue;
+                }
+                if histogram.len() <= distance{
+                    histogram.resize(distance + 1, 0);
+                }
+                histogram[distance] += 1;
+                reachable_pairs += 1;
+                total_distance += distance;
+            }
+        }
+        DistanceSample{
+            sources:sources.len(),
+            reachable_pairs,
+            unreachable_pairs:sources.len() * self.n.saturating_sub(1) - reachable_pairs,
+            average_path_length:if reachable_pairs == 0 { 0.0 } else { total_distance as f64 / reachable_pairs as f64 },
+            histogram
+        }
+    }
+}
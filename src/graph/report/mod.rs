@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::Path;
+use crate::graph::*;
+use crate::graph::component_functions::{count_components,get_component_scale};
+use crate::graph::export::json_string;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+//Component statistics of the subgraph holding one research subject
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubjectStats{
+    pub subject:String,
+    pub nodes:usize,
+    pub edges:usize,
+    pub num_components:usize,
+    //Fraction of the subject's papers inside its largest component
+    pub largest_component_share:f64,
+    //Clustering of the subject's undirected citations, see Graph::clustering
+    pub triangles:usize,
+    pub transitivity:f64,
+    pub average_clustering:f64
+}
+
+//Everything a run of the analysis produces, ready to be written for dashboards
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalysisReport{
+    pub nodes:usize,
+    pub edges:usize,
+    pub num_components:usize,
+    //Sorted largest first
+    pub component_sizes:Vec<usize>,
+    //Aggregate share of the graph captured by the k largest components, from get_component_scale
+    pub aggregation:Vec<f64>,
+    pub triangles:usize,
+    pub transitivity:f64,
+    pub average_clustering:f64,
+    //Sorted by subject name
+    pub subjects:Vec<SubjectStats>
+}
+
+impl AnalysisReport{
+    pub fn from_graph(graph:&Graph) -> Self{
+        let (component, num_components) = graph.connected_components();
+        let mut component_sizes = count_components(&component, num_components);
+        component_sizes.sort_by_key(|size| std::cmp::Reverse(*size));
+        let aggregation = get_component_scale(&component, num_components, true);
+
+        let subgraphs = graph.calculate_subgraphs();
+        //Each subject is independent, so with the `parallel` feature they are analysed on the rayon pool
+        #[cfg(feature = "parallel")]
+        let subgraphs = subgraphs.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let subgraphs = subgraphs.iter();
+        let mut subjects: Vec<SubjectStats> = subgraphs.map(|(subject, subgraph)| {
+            let (component, num_components) = subgraph.connected_components();
+            let scale = get_component_scale(&component, num_components, true);
+            let clustering = subgraph.clustering();
+            SubjectStats{
+                subject:subject.clone(),
+                nodes:subgraph.n,
+                edges:subgraph.calc_num_edges(),
+                num_components,
+                largest_component_share:scale.get(1).copied().unwrap_or(0.0),
+                triangles:clustering.total_triangles,
+                transitivity:clustering.transitivity,
+                average_clustering:clustering.average_clustering
+            }
+        }).collect();
+        subjects.sort_by(|a, b| a.subject.cmp(&b.subject));
+        let clustering = graph.clustering();
+
+        AnalysisReport{
+            nodes:graph.n,
+            edges:graph.calc_num_edges(),
+            num_components,
+            component_sizes,
+            aggregation,
+            triangles:clustering.total_triangles,
+            transitivity:clustering.transitivity,
+            average_clustering:clustering.average_clustering,
+            subjects
+        }
+    }
+
+    pub fn largest_component_share(&self) -> f64{
+        self.aggregation.get(1).copied().unwrap_or(0.0)
+    }
+
+    pub fn to_json(&self) -> String{
+        let sizes: Vec<String> = self.component_sizes.iter().map(|size| size.to_string()).collect();
+        let aggregation: Vec<String> = self.aggregation.iter().map(|share| json_number(*share)).collect();
+        let subjects: Vec<String> = self.subjects.iter().map(|stats| format!(
+            "    {{\"subject\": {}, \"nodes\": {}, \"edges\": {}, \"num_components\": {}, \"largest_component_share\": {}, \"triangles\": {}, \"transitivity\": {}, \"average_clustering\": {}}}",
+            json_string(&stats.subject), stats.nodes, stats.edges, stats.num_components, json_number(stats.largest_component_share),
+            stats.triangles, json_number(stats.transitivity), json_number(stats.average_clustering)
+        )).collect();
+        format!(
+            "{{\n  \"nodes\": {},\n  \"edges\": {},\n  \"num_components\": {},\n  \"largest_component_share\": {},\n  \"triangles\": {},\n  \"transitivity\": {},\n  \"average_clustering\": {},\n  \"component_sizes\": [{}],\n  \"aggregation\": [{}],\n  \"subjects\": [\n{}\n  ]\n}}\n",
+            self.nodes, self.edges, self.num_components, json_number(self.largest_component_share()),
+            self.triangles, json_number(self.transitivity), json_number(self.average_clustering),
+            sizes.join(", "), aggregation.join(", "), subjects.join(",\n")
+        )
+    }
+
+    pub fn write_json(&self, output_file:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>>{
+        fs::write(output_file, self.to_json())?;
+        Ok(())
+    }
+
+    //Long format csv with one value per row: scope ("all" or a subject), metric, rank (for per-component values) and value
+    pub fn write_csv(&self, output_file:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>>{
+        let mut writer = csv::Writer::from_path(output_file)?;
+        writer.write_record(["scope", "metric", "rank", "value"])?;
+        writer.write_record(["all", "nodes", "", &self.nodes.to_string()])?;
+        writer.write_record(["all", "edges", "", &self.edges.to_string()])?;
+        writer.write_record(["all", "num_components", "", &self.num_components.to_string()])?;
+        writer.write_record(["all", "largest_component_share", "", &self.largest_component_share().to_string()])?;
+        writer.write_record(["all", "triangles", "", &self.triangles.to_string()])?;
+        writer.write_record(["all", "transitivity", "", &self.transitivity.to_string()])?;
+        writer.write_record(["all", "average_clustering", "", &self.average_clustering.to_string()])?;
+        for (rank, size) in self.component_sizes.iter().enumerate(){
+            writer.write_record(["all", "component_size", &(rank + 1).to_string(), &size.to_string()])?;
+        }
+        for (rank, share) in self.aggregation.iter().enumerate(){
+            writer.write_record(["all", "aggregation", &rank.to_string(), &share.to_string()])?;
+        }
+        for stats in self.subjects.iter(){
+            writer.write_record([stats.subject.as_str(), "nodes", "", &stats.nodes.to_string()])?;
+            writer.write_record([stats.subject.as_str(), "edges", "", &stats.edges.to_string()])?;
+            writer.write_record([stats.subject.as_str(), "num_components", "", &stats.num_components.to_string()])?;
+            writer.write_record([stats.subject.as_str(), "largest_component_share", "", &stats.largest_component_share.to_string()])?;
+            writer.write_record([stats.subject.as_str(), "triangles", "", &stats.triangles.to_string()])?;
+            writer.write_record([stats.subject.as_str(), "transitivity", "", &stats.transitivity.to_string()])?;
+            writer.write_record([stats.subject.as_str(), "average_clustering", "", &stats.average_clustering.to_string()])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+//JSON has no NaN or infinity, so those are written as null
+fn json_number(value:f64) -> String{
+    if value.is_finite() { value.to_string() } else { String::from("null") }
+}
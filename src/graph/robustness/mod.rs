@@ -0,0 +1,99 @@
+use rand::seq::SliceRandom;
+use crate::graph::*;
+use crate::graph::union_find::DisjointSet;
+
+//Order in which papers are knocked out of the graph
+#[derive(Debug,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RemovalStrategy{
+    //Random failures
+    Random,
+    //Targeted attack on the papers with the most citations made plus received (ignoring direction), computed once up front
+    Degree,
+    //Highest score first, indexed by mapped node (e.g. degree_centrality or coreness)
+    Ranking(Vec<f64>)
+}
+
+//State of the graph after `removed` nodes (or edges) have been taken out
+#[derive(Debug,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RobustnessPoint{
+    pub removed:usize,
+    pub fraction_removed:f64,
+    pub num_components:usize,
+    //Size of the largest component over the original number of papers, so the first point
+    //matches get_component_scale and the curve falls to 0 as papers disappear
+    pub giant_share:f64
+}
+
+impl Graph{
+    pub fn removal_order(&self, strategy:&RemovalStrategy) -> Vec<Vertex>{
+        let mut order: Vec<Vertex> = (0..self.n).collect();
+        match strategy{
+            RemovalStrategy::Random => order.shuffle(&mut rand::thread_rng()),
+            RemovalStrategy::Degree => {
+                let degrees: Vec<usize> = self.undirected_adjacency().iter().map(|neighbors| neighbors.len()).collect();
+                order.sort_by_key(|v| std::cmp::Reverse(degrees[*v]));
+            },
+            RemovalStrategy::Ranking(scores) => {
+                //NaN scores go last
+                order.sort_by(|a, b| scores[*b].partial_cmp(&scores[*a]).unwrap_or_else(|| scores[*a].is_nan().cmp(&scores[*b].is_nan())));
+            }
+        }
+        order
+    }
+
+    //Remove papers one at a time and record the giant component after each step (n + 1 points, starting
+    //with the intact graph). The papers are added back in reverse order into a union-find, so the whole
+    //curve costs about as much as one pass over the edges.
+    pub fn node_robustness(&self, strategy:&RemovalStrategy) -> Vec<RobustnessPoint>{
+        let order = self.removal_order(strategy);
+        let adj_list = self.undirected_adjacency();
+        let mut present = vec![false;self.n];
+        let mut sets = DisjointSet::new(self.n);
+        let mut largest = 0;
+        let mut num_components = 0;
+        let mut points = vec![self.robustness_point(self.n, 0, 0, self.n)];
+        for (removed, v) in order.iter().enumerate().rev(){
+            present[*v] = true;
+            num_components += 1;
+            for w in adj_list[*v].iter(){
+                if present[*w] && sets.union(*v, *w){
+                    num_components -= 1;
+                }
+            }
+            largest = largest.max(sets.set_size(*v));
+            points.push(self.robustness_point(removed, num_components, largest, self.n));
+        }
+        points.reverse();
+        points
+    }
+
+    //Remove citations in random order (bond percolation), one point per removed citation plus the intact graph
+    pub fn edge_robustness(&self) -> Vec<RobustnessPoint>{
+        let mut edges: Vec<Edge> = self.outedges.iter().enumerate()
+            .flat_map(|(v, neighbors)| neighbors.iter().map(move |w| (v, *w)))
+            .collect();
+        edges.shuffle(&mut rand::thread_rng());
+        let mut sets = DisjointSet::new(self.n);
+        let mut largest = if self.n > 0 { 1 } else { 0 };
+        let mut points = vec![self.robustness_point(edges.len(), self.n, largest, edges.len())];
+        for (removed, (v, w)) in edges.iter().enumerate().rev(){
+            if sets.union(*v, *w){
+                largest = largest.max(sets.set_size(*v));
+            }
+            points.push(self.robustness_point(removed, sets.num_sets(), largest, edges.len()));
+        }
+        points.reverse();
+        points
+    }
+
+    fn robustness_point(&self, removed:usize, num_components:usize, largest:usize, total:usize) -> RobustnessPoint{
+        RobustnessPoint{
+            removed,
+            fraction_removed:if total == 0 { 0.0 } else { removed as f64 / total as f64 },
+            num_components,
+            giant_share:if self.n == 0 { 0.0 } else { largest as f64 / self.n as f64 }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead,BufReader,Read};
+use std::rc::Rc;
+use crate::graph::*;
+use crate::graph::cleaning::{CleaningPolicy,DataQualityReport,resolve_edge};
+
+//Citations read between two progress reports
+pub const PROGRESS_INTERVAL: usize = 1_000_000;
+
+//How far a streaming load has got. The edge file is read twice: pass 1 counts the out-degrees,
+//pass 2 places every citation.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct LoadProgress{
+    pub pass:usize,
+    pub edges:usize,
+    //Bytes of the file on disk, so compressed input is measured before decompression
+    pub bytes_read:u64,
+    pub total_bytes:u64
+}
+impl LoadProgress{
+    //Share of the current pass done
+    pub fn fraction(&self) -> f64{
+        if self.total_bytes == 0 { 1.0 } else { self.bytes_read as f64 / self.total_bytes as f64 }
+    }
+}
+
+//Open a file for reading, decompressing .gz and .zst files when the gzip and zstd features are enabled
+pub fn open_input(path:impl AsRef<Path>) -> Result<Box<dyn Read>,String>{
+    open_counted(path.as_ref(), Rc::new(Cell::new(0)))
+}
+
+fn open_counted(path:&Path, bytes_read:Rc<Cell<u64>>) -> Result<Box<dyn Read>,String>{
+    let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let reader = BufReader::new(CountingReader{inner:file, bytes_read});
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str(){
+        "gz" => gzip_decoder(path, reader),
+        "zst" | "zstd" => zstd_decoder(path, reader),
+        _ => Ok(Box::new(reader))
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_decoder(_path:&Path, reader:impl BufRead + 'static) -> Result<Box<dyn Read>,String>{
+    //Multi-member so files made by concatenating .gz chunks are read to the end
+    Ok(Box::new(flate2::bufread::MultiGzDecoder::new(reader)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_decoder(path:&Path, _reader:impl BufRead + 'static) -> Result<Box<dyn Read>,String>{
+    Err(format!("{} is gzip compressed, rebuild with the gzip feature to read it", path.display()))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decoder(path:&Path, reader:impl BufRead + 'static) -> Result<Box<dyn Read>,String>{
+    let decoder = zstd::stream::read::Decoder::with_buffer(reader).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    Ok(Box::new(decoder))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decoder(path:&Path, _reader:impl BufRead + 'static) -> Result<Box<dyn Read>,String>{
+    Err(format!("{} is zstd compressed, rebuild with the zstd feature to read it", path.display()))
+}
+
+//Counts the bytes taken from the file, before any decompression
+struct CountingReader<R>{
+    inner:R,
+    bytes_read:Rc<Cell<u64>>
+}
+impl<R:Read> Read for CountingReader<R>{
+    fn read(&mut self, buf:&mut [u8]) -> std::io::Result<usize>{
+        let read = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + read as u64);
+        Ok(read)
+    }
+}
+
+impl Graph{
+    //Load the csv pair without ever holding the citations as a list of pairs. Pass 1 over the edge file resolves
+    //the endpoints and counts out-degrees, pass 2 writes every citation straight into its slot of a compressed
+    //sparse row array, which is then split into the adjacency lists. Either file may be .gz or .zst compressed.
+    //The cleaning policy works as in from_csvs_cleaned, except that duplicate examples are listed by citing
+    //paper rather than in file order. `progress` is called every PROGRESS_INTERVAL citations and after each pass.
+    pub fn from_csvs_streaming(
+        edge_path:impl AsRef<Path>,
+        node_path:impl AsRef<Path>,
+        policy:&CleaningPolicy,
+        mut progress:impl FnMut(&LoadProgress)
+    ) -> Result<(Self, DataQualityReport),String>{
+        let edge_path = edge_path.as_ref();
+        let ((mut node_data, mut reverse_map), repeated) = Graph::read_node_csv(node_path.as_ref())?;
+        let mut report = DataQualityReport{policy:*policy, nodes_read:node_data.len() + repeated.len(), ..DataQualityReport::default()};
+        for id in repeated{
+            report.duplicate_nodes.record(id);
+        }
+
+        //Pass 1: out-degrees, creating or dropping missing papers on the way
+        let mut degrees: Vec<usize> = vec![0;node_data.len()];
+        let mut missing_nodes = HashSet::new();
+        let edges_read = for_each_citation(edge_path, 1, &mut progress, |citing, cited| {
+            if let Some((v, _)) = resolve_edge(&mut node_data, &mut reverse_map, citing, cited, &mut missing_nodes, &mut report)?{
+                if v >= degrees.len(){
+                    degrees.resize(node_data.len(), 0);
+                }
+                degrees[v] += 1;
+            }
+            Ok(())
+        })?;
+        report.edges_read = edges_read;
+        let n = node_data.len();
+        degrees.resize(n, 0);
+        report.missing_nodes = missing_nodes.into_iter().collect();
+        report.missing_nodes.sort();
+
+        //Pass 2: every paper exists now, so a citation is kept exactly when it was counted in pass 1
+        let mut offsets = Vec::with_capacity(n + 1);
+        offsets.push(0);
+        for degree in degrees.iter(){
+            offsets.push(offsets.last().unwrap() + degree);
+        }
+        let mut targets: Vec<Vertex> = vec![0;offsets[n]];
+        let mut next = offsets.clone();
+        let changed = || format!("{} changed between the two passes", edge_path.display());
+        for_each_citation(edge_path, 2, &mut progress, |citing, cited| {
+            if let (Some(v), Some(w)) = (node_data.get(&citing), node_data.get(&cited)){
+                let (v, w) = (v.mapped_node, w.mapped_node);
+                if v != w || !policy.drop_self_loops{
+                    if next[v] >= offsets[v + 1]{
+                        return Err(changed());
+                    }
+                    targets[next[v]] = w;
+                    next[v] += 1;
+                }
+            }
+            Ok(())
+        })?;
+        if next[..n] != offsets[1..]{
+            return Err(changed());
+        }
+
+        //Find repeated citations per paper, keeping the first copy when deduping
+        let mut last_citing = vec![usize::MAX;n];
+        let mut outedges: AdjacencyList = Vec::with_capacity(n);
+        for v in 0..n{
+            let mut neighbors = Vec::with_capacity(degrees[v]);
+            for w in targets[offsets[v]..offsets[v + 1]].iter().copied(){
+                if last_citing[w] == v{
+                    report.duplicate_edges.record((reverse_map[&v], reverse_map[&w]));
+                    if policy.dedupe{
+                        continue;
+                    }
+                }
+                last_citing[w] = v;
+                neighbors.push(w);
+            }
+            neighbors.shrink_to_fit();
+            outedges.push(neighbors);
+        }
+        report.edges_kept = outedges.iter().map(|neighbors| neighbors.len()).sum();
+        Ok((Graph{n, outedges, node_data, reverse_map}, report))
+    }
+}
+
+//Call `f` with the (citing, cited) original ids of every row of an edge csv, returning the number of rows
+fn for_each_citation(
+    edge_path:&Path,
+    pass:usize,
+    progress:&mut impl FnMut(&LoadProgress),
+    mut f:impl FnMut(usize, usize) -> Result<(),String>
+) -> Result<usize,String>{
+    let total_bytes = std::fs::metadata(edge_path).map_err(|e| format!("Could not open {}: {}", edge_path.display(), e))?.len();
+    let bytes_read = Rc::new(Cell::new(0));
+    let mut edge_rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(open_counted(edge_path, bytes_read.clone())?);
+    let mut record = csv::StringRecord::new();
+    let mut edges = 0;
+    let status = |edges:usize| LoadProgress{pass, edges, bytes_read:bytes_read.get(), total_bytes};
+    loop{
+        match edge_rdr.read_record(&mut record){
+            Ok(true) => {},
+            Ok(false) => break,
+            Err(e) => return Err(format!("Could not read {}: {}", edge_path.display(), e))
+        }
+        let line = record.position().map_or(0, |position| position.line());
+        let id = |field:usize| record.get(field).and_then(|id| id.trim().parse::<usize>().ok())
+            .ok_or_else(|| format!("Line {} of {} does not contain two numeric node ids", line, edge_path.display()));
+        f(id(1)?, id(2)?)?;
+        edges += 1;
+        if edges % PROGRESS_INTERVAL == 0{
+            progress(&status(edges));
+        }
+    }
+    progress(&status(edges));
+    Ok(edges)
+}
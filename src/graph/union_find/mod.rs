@@ -0,0 +1,64 @@
+//Disjoint sets over 0..n with union by size and path halving
+#[derive(Debug,Clone,Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisjointSet{
+    parent:Vec<usize>,
+    size:Vec<usize>,
+    num_sets:usize
+}
+
+impl DisjointSet{
+    //n singleton sets
+    pub fn new(n:usize) -> Self{
+        DisjointSet{parent:(0..n).collect(), size:vec![1;n], num_sets:n}
+    }
+
+    pub fn find(&mut self, mut x:usize) -> usize{
+        while self.parent[x] != x{
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    //Merge the sets holding a and b, returning false when they were already one set
+    pub fn union(&mut self, a:usize, b:usize) -> bool{
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b{
+            return false;
+        }
+        if self.size[a] < self.size[b]{
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.num_sets -= 1;
+        true
+    }
+
+    //Size of the set holding x
+    pub fn set_size(&mut self, x:usize) -> usize{
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    pub fn num_sets(&self) -> usize{
+        self.num_sets
+    }
+
+    //Append a new singleton set and return its element
+    pub fn push(&mut self) -> usize{
+        self.parent.push(self.parent.len());
+        self.size.push(1);
+        self.num_sets += 1;
+        self.parent.len() - 1
+    }
+
+    //Size of every set, in order of their root elements
+    pub fn set_sizes(&self) -> Vec<usize>{
+        self.parent.iter().enumerate()
+            .filter(|(x, parent)| *x == **parent)
+            .map(|(x, _)| self.size[x])
+            .collect()
+    }
+}
@@ -1,11 +1,22 @@
 use plotters::prelude::*;
 use rand::Rng;
 use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
 use crate::graph::component_functions::count_components;
+use crate::graph::Graph;
+use crate::graph::kcore::CoreLevel;
+use crate::graph::robustness::RobustnessPoint;
 
-pub fn show_aggregation(points:&Vec<f64>,filename:&str) -> Result<(), Box<dyn std::error::Error>> {
+//Grid cell -> circles (center x, center y, radius) already placed in that cell
+type PlacementGrid = HashMap<(usize, usize), Vec<(f64, f64, f64)>>;
+
+//Chart coordinates (x_min, x_max, y_min, y_max) used for every connectivity plot
+pub const DRAWING_AREA: (i32, i32, i32, i32) = (-500, 500, -500, 500);
+
+pub fn show_aggregation(points:&[f64],filename:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
     // Create the drawing area, using the provided filename
-    let root = BitMapBackend::new(filename, (640, 480)).into_drawing_area();
+    let root = BitMapBackend::new(filename.as_ref(), (640, 480)).into_drawing_area();
     root.fill(&WHITE)?;
 
     // Change Title Name Later
@@ -36,8 +47,89 @@ pub fn show_aggregation(points:&Vec<f64>,filename:&str) -> Result<(), Box<dyn st
     Ok(())
 }
 
-pub fn get_graph_dimensions(
-    component: &Vec<Option<usize>>, 
+//Giant component share against the fraction of papers (or citations) removed, one line per named curve
+pub fn show_robustness(curves:&[(&str, &[RobustnessPoint])], filename:impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let root = BitMapBackend::new(filename.as_ref(), (640, 480)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root_area = root.titled("Giant Component Under Removal", ("sans-serif", 40))?;
+
+    let mut cc = ChartBuilder::on(&root_area)
+        .margin(5)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0.0..1.0, 0.0..1.05)?;
+    cc.configure_mesh()
+        .disable_mesh()
+        .x_desc("Fraction removed")
+        .y_desc("Giant component share")
+        .x_label_formatter(&|v: &f64| format!("{:.1}", v))
+        .y_label_formatter(&|v: &f64| format!("{:.1}", v))
+        .draw()?;
+
+    for (index, (name, points)) in curves.iter().enumerate(){
+        let color = Palette99::pick(index).to_rgba();
+        cc.draw_series(LineSeries::new(
+            points.iter().map(|point| (point.fraction_removed, point.giant_share)),
+            color,
+        ))?
+        .label(*name)
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+    cc.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+    Ok(())
+}
+
+//Component count (top) and giant component share (bottom) of the k-core as k rises
+pub fn show_core_profile(levels:&[CoreLevel], filename:impl AsRef<Path>, title:&str) -> Result<(), Box<dyn std::error::Error>> {
+    let root = BitMapBackend::new(filename.as_ref(), (640, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root_area = root.titled(title, ("sans-serif", 30))?;
+    let panels = root_area.split_evenly((2, 1));
+    let max_k = levels.last().map(|level| level.k).unwrap_or(0) as isize;
+    let max_components = levels.iter().map(|level| level.num_components).max().unwrap_or(0);
+
+    let mut components_chart = ChartBuilder::on(&panels[0])
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0..max_k + 1, 0..max_components + 1)?;
+    components_chart.configure_mesh()
+        .disable_mesh()
+        .x_desc("k")
+        .y_desc("Components")
+        .draw()?;
+    components_chart.draw_series(LineSeries::new(
+        levels.iter().map(|level| (level.k as isize, level.num_components)),
+        &BLUE,
+    ).point_size(3))?;
+
+    let mut share_chart = ChartBuilder::on(&panels[1])
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0..max_k + 1, 0.0..1.05)?;
+    share_chart.configure_mesh()
+        .disable_mesh()
+        .x_desc("k")
+        .y_desc("Giant component share")
+        .y_label_formatter(&|v: &f64| format!("{:.1}", v))
+        .draw()?;
+    share_chart.draw_series(LineSeries::new(
+        levels.iter().map(|level| (level.k as isize, level.giant_share)),
+        &RED,
+    ).point_size(3))?;
+
+    root.present()?;
+    Ok(())
+}
+
+pub(crate) fn get_graph_dimensions(
+    component: &[Option<usize>], 
     num_components: usize, 
     drawing_bounds: (i32, i32, i32, i32),
     biggest_circle: f64,
@@ -52,8 +144,7 @@ pub fn get_graph_dimensions(
         .enumerate()
         .map(|(index, value)| (index, *value))
         .collect();
-    component_sorted_tuples.sort_by(|a, b| b.1.cmp(&a.1));
-    println!("{:?}",component_sorted_tuples);
+    component_sorted_tuples.sort_by_key(|tuple| std::cmp::Reverse(tuple.1));
     let mut current_x = x_min as f64;
     let total_x_space = (x_max - x_min) as f64;
 
@@ -61,7 +152,7 @@ pub fn get_graph_dimensions(
     let total_y_space = (y_max - y_min) as f64;
 
     let mut range_hash = HashMap::<usize, ((f64, f64), f64)>::new();
-    let mut grid: HashMap<(usize, usize), Vec<(f64, f64, f64)>> = HashMap::new();
+    let mut grid: PlacementGrid = HashMap::new();
 
     for (index, value) in component_sorted_tuples {
         let percentage_needed = value as f64 / total as f64;
@@ -126,8 +217,31 @@ pub fn get_graph_dimensions(
     range_hash
 }
 
+//Place every node at a random point inside its component's circle, indexed by mapped node
+pub fn layout_components(
+    component: &[Option<usize>],
+    num_components: usize,
+    drawing_bounds: (i32, i32, i32, i32),
+    biggest_circle: f64,
+) -> Vec<Option<(i32, i32)>> {
+    let range_hash = get_graph_dimensions(component, num_components, drawing_bounds, biggest_circle, 50.0);
+    let mut rng = rand::thread_rng();
+    component.iter().map(|comp| {
+        //Switch back from 1 based to 0 based index
+        let (center, radius) = range_hash.get(&((*comp)? - 1))?;
+        let (center_x, center_y) = *center;
+
+        // Generate random position within the circle using polar coordinates
+        let random_angle = rng.gen_range(0.0..(2.0 * std::f64::consts::PI)); // Random angle
+        let random_radius = rng.gen_range(0.0..=*radius); // Random radius (uniform distribution)
 
-pub fn interpolate_color(start_color: (u8, u8, u8), end_color: (u8, u8, u8), t: f64) -> (u8, u8, u8) {
+        let node_x = (center_x + random_radius * random_angle.cos()) as i32;
+        let node_y = (center_y + random_radius * random_angle.sin()) as i32;
+        Some((node_x, node_y))
+    }).collect()
+}
+
+pub(crate) fn interpolate_color(start_color: (u8, u8, u8), end_color: (u8, u8, u8), t: f64) -> (u8, u8, u8) {
     let (r_start, g_start, b_start) = start_color;
     let (r_end, g_end, b_end) = end_color;
 
@@ -138,7 +252,7 @@ pub fn interpolate_color(start_color: (u8, u8, u8), end_color: (u8, u8, u8), t:
     (r, g, b)
 }
 
-pub fn get_color_from_gradient(index: usize, total: usize) -> RGBAColor {
+pub(crate) fn get_color_from_gradient(index: usize, total: usize) -> RGBAColor {
     // Define the start and end colors (dark blue to teal)
     let dark_blue = (0, 0, 139);  // RGB for dark blue
     let teal = (0, 128, 128);    // RGB for teal
@@ -150,3 +264,109 @@ pub fn get_color_from_gradient(index: usize, total: usize) -> RGBAColor {
     let (r,g,b) = interpolate_color(dark_blue, teal, t);
     RGBAColor(r,g,b,1.0)
 }
+
+
+//What drives the radius of each node in visualize_connectivity_styled
+#[derive(Debug,Clone)]
+pub enum NodeSizing{
+    //Every node gets the same radius (in pixels)
+    Fixed(u32),
+    //Times a paper is cited
+    InDegree,
+    //Number of references a paper makes
+    OutDegree,
+    //Any per-node score indexed by mapped node (e.g. a centrality measure)
+    Metric(Vec<f64>)
+}
+
+#[derive(Debug,Clone)]
+pub struct DrawStyle{
+    pub node_sizing:NodeSizing,
+    //Smallest and largest radius used when scaling by a metric
+    pub radius_range:(u32,u32),
+    //Draw edges as arrows from the citing paper to the cited paper
+    pub arrows:bool,
+    //Length of the arrow head in chart units
+    pub arrow_size:f64,
+    //Opacity of each edge, so dense regions blend into darker areas
+    pub edge_alpha:f64,
+    //Draw a random sample of this many edges when the graph has more
+    pub max_edges:Option<usize>,
+    //Mapped nodes ringed in red on top of the plot, e.g. articulation points
+    pub highlight_nodes:Vec<usize>,
+    //Mapped node pairs whose citations (in either direction) are drawn in red, e.g. bridges
+    pub highlight_edges:Vec<(usize,usize)>
+}
+impl Default for DrawStyle{
+    fn default() -> Self{
+        DrawStyle{
+            node_sizing:NodeSizing::Fixed(5),
+            radius_range:(3,12),
+            arrows:true,
+            arrow_size:12.0,
+            edge_alpha:0.35,
+            max_edges:None,
+            highlight_nodes:Vec::new(),
+            highlight_edges:Vec::new()
+        }
+    }
+}
+
+pub fn node_radii(graph:&Graph, sizing:&NodeSizing, radius_range:(u32,u32)) -> Vec<u32>{
+    let metric:Vec<f64> = match sizing{
+        NodeSizing::Fixed(radius) => return vec![*radius;graph.n],
+        NodeSizing::InDegree => graph.in_degrees().iter().map(|d| *d as f64).collect(),
+        NodeSizing::OutDegree => graph.out_degrees().iter().map(|d| *d as f64).collect(),
+        //Missing or negative entries are drawn at the minimum radius
+        NodeSizing::Metric(values) => (0..graph.n).map(|i| values.get(i).copied().unwrap_or(0.0).max(0.0)).collect()
+    };
+    let (min_radius, max_radius) = radius_range;
+    let max_value = metric.iter().cloned().fold(0.0, f64::max);
+    metric.iter().map(|value| {
+        if max_value <= 0.0{
+            return min_radius;
+        }
+        //Square root scaling keeps the circle area proportional to the metric
+        let t = (value / max_value).sqrt();
+        (min_radius as f64 + t * (max_radius as f64 - min_radius as f64)).round() as u32
+    }).collect()
+}
+
+//Pull the end of a segment back towards its start by `amount`, leaving the start untouched
+pub(crate) fn shorten_segment(from:(i32,i32), to:(i32,i32), amount:f64) -> (i32,i32){
+    let (dx, dy) = ((to.0 - from.0) as f64, (to.1 - from.1) as f64);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length <= amount{
+        return to;
+    }
+    let scale = (length - amount) / length;
+    (from.0 + (dx * scale).round() as i32, from.1 + (dy * scale).round() as i32)
+}
+
+//Triangle (tip, left wing, right wing) for an arrow head ending at `to`, None for zero length edges
+pub(crate) fn arrow_head(from:(i32,i32), to:(i32,i32), size:f64) -> Option<[(i32,i32);3]>{
+    let (dx, dy) = ((to.0 - from.0) as f64, (to.1 - from.1) as f64);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0{
+        return None;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let base = (to.0 as f64 - ux * size, to.1 as f64 - uy * size);
+    let half_width = size / 2.0;
+    let left = ((base.0 - uy * half_width).round() as i32, (base.1 + ux * half_width).round() as i32);
+    let right = ((base.0 + uy * half_width).round() as i32, (base.1 - ux * half_width).round() as i32);
+    Some([to, left, right])
+}
+
+//Wall-clock breakdown of a render_connectivity call
+#[derive(Debug,Clone)]
+pub struct RenderTimings{
+    //Canvas, title and axes
+    pub setup:Duration,
+    pub edges:Duration,
+    pub nodes:Duration,
+    pub total:Duration,
+    pub edges_drawn:usize,
+    //Edges left out by the max_edges sample
+    pub edges_skipped:usize
+}
\ No newline at end of file
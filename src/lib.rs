@@ -0,0 +1,23 @@
+//Citation network connectivity analysis: load a citation graph, find and profile its connected components
+//and plot them. The command line tool in main.rs is a thin wrapper around this crate.
+mod graph;
+
+pub use graph::{Graph,NodeData,Vertex,Edge,AdjacencyList,Component,Point};
+//Free functions stay grouped in their modules
+pub use graph::{component_functions,visualization_support,export};
+//Options and results of the Graph methods
+pub use graph::paths::{Direction,DistanceSample};
+pub use graph::dag::CyclicComponent;
+pub use graph::main_path::{TraversalWeight,MainPathSearch,MainPath};
+pub use graph::coupling::DerivedGraph;
+pub use graph::kcore::{CoreDegree,CoreLevel};
+pub use graph::clustering::ClusteringStats;
+pub use graph::cut_points::{CutVertex,CutStructure};
+pub use graph::report::{AnalysisReport,SubjectStats};
+pub use graph::robustness::{RemovalStrategy,RobustnessPoint};
+pub use graph::union_find::DisjointSet;
+pub use graph::connectivity::{IncrementalConnectivity,edge_feed};
+pub use graph::import::{DEFAULT_LABEL,DEFAULT_SUBJECT};
+pub use graph::cache::{CacheValidation,CACHE_VERSION};
+pub use graph::cleaning::{CleaningPolicy,MissingEndpoints,DataQualityReport,Issue,MAX_EXAMPLES};
+pub use graph::streaming::{LoadProgress,PROGRESS_INTERVAL,open_input};
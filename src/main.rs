@@ -1,29 +1,697 @@
-//Import all graph functions
-mod graph;
-use graph::Graph;
-use graph::component_functions::*;
-use graph::visualization_support::show_aggregation;
-fn main() {
-    let graph = Graph::from_csvs("citation_network\\edges.csv","citation_network\\nodes.csv").unwrap();
-    let (component,num_components) = graph.connected_components();
-    let component_sizes = count_components(&component, num_components);
-    let component_scale = get_component_scale(&component, num_components,true);
-
-    println!("Component Sizes: {:?}",component_sizes);
-    println!("Component Scale: {:?}",component_scale);
-    //Plot the component effectiveness
-    show_aggregation(&component_scale, "plots\\component_aggregation.png").expect("Error in Aggregate Image Creation");
-    //REALLY cool custom visual that shows the connectivity of the graph components
-    graph.visualize_connectivity("plots\\connected_components.png", 3.0, (1024,1024),"All Research Connected Components").unwrap();
-
-    let subgraphs = graph.calculate_subgraphs();
-    for (subject, subgraph) in subgraphs.iter(){
-        let (component, num_components) = subgraph.connected_components();
-        let component_scale = get_component_scale(&component, num_components, true);
-        println!("Papers in {} have {} components. {:.2} of the data is captured in one component.",subject,num_components,component_scale[1]);
-        subgraph.visualize_connectivity(
-            &format!("plots\\subgraphs\\{}_connectivity.png",subject),
-            3.0, (1024,1024), 
-            &format!("Connectivity of Research Papers in {}",subject)).unwrap();
+use std::error::Error;
+use std::path::{Path,PathBuf};
+use clap::{Args,Parser,Subcommand,ValueEnum};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use citation_connectivity::{Graph,AnalysisReport,Direction,TraversalWeight,MainPathSearch,CoreDegree,RemovalStrategy,RobustnessPoint};
+use citation_connectivity::{IncrementalConnectivity,edge_feed,CacheValidation,CleaningPolicy,MissingEndpoints,DataQualityReport};
+use citation_connectivity::component_functions::*;
+use citation_connectivity::export::{write_html,write_graphml,write_gexf,write_dot,write_component_assignments,ExportOptions};
+use citation_connectivity::visualization_support::{show_aggregation,show_core_profile,show_robustness,layout_components,DrawStyle,NodeSizing,DRAWING_AREA};
+
+#[derive(Parser)]
+#[command(name = "citation-connectivity", about = "Connectivity analysis and visualization of citation networks")]
+struct Cli{
+    #[command(subcommand)]
+    command:Command
+}
+
+#[derive(Subcommand)]
+enum Command{
+    /// Print component sizes and plot how much of the graph the largest components capture
+    Components{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Output png for the aggregation curve
+        #[arg(long, default_value = "plots/component_aggregation.png")]
+        output:PathBuf
+    },
+    /// Split the graph by research subject and plot the connectivity of each subject
+    Subgraphs{
+        #[command(flatten)]
+        input:InputArgs,
+        #[command(flatten)]
+        plot:PlotArgs,
+        /// Directory that receives one <subject>_connectivity.png per subject
+        #[arg(long, default_value = "plots/subgraphs")]
+        output_dir:PathBuf
+    },
+    /// Plot the connected components of the whole graph
+    Visualize{
+        #[command(flatten)]
+        input:InputArgs,
+        #[command(flatten)]
+        plot:PlotArgs,
+        /// Output png
+        #[arg(long, default_value = "plots/connected_components.png")]
+        output:PathBuf,
+        /// Title drawn above the plot
+        #[arg(long, default_value = "All Research Connected Components")]
+        title:String,
+        /// Metric that drives the node radius
+        #[arg(long, value_enum, default_value_t = SizeBy::InDegree)]
+        size_by:SizeBy,
+        /// Draw a random sample of this many edges on larger graphs
+        #[arg(long)]
+        max_edges:Option<usize>,
+        /// Draw plain lines instead of arrows
+        #[arg(long)]
+        no_arrows:bool,
+        /// Ring articulation points and draw bridges in red
+        #[arg(long)]
+        highlight_cuts:bool
+    },
+    /// Print node, edge and component counts, optionally writing the full report as JSON and CSV
+    Stats{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Write the report as JSON
+        #[arg(long)]
+        json:Option<PathBuf>,
+        /// Write the report as a long format CSV (scope, metric, rank, value)
+        #[arg(long)]
+        csv:Option<PathBuf>,
+        /// Estimate the average path length from this many random BFS sources
+        #[arg(long)]
+        path_samples:Option<usize>
+    },
+    /// Print the shortest citation chain between two papers
+    Chain{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Original id of the first paper
+        #[arg(long)]
+        from:usize,
+        /// Original id of the last paper
+        #[arg(long)]
+        to:usize,
+        /// Allow the chain to follow citations in either direction
+        #[arg(long)]
+        either_direction:bool
+    },
+    /// Check that citations only point backwards in time, listing the cycles that break this
+    Cycles{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Print the example cycles of at most this many cyclic components
+        #[arg(long, default_value_t = 10)]
+        max_cycles:usize,
+        /// Remove a small set of citations so that no cycles remain and print them
+        #[arg(long)]
+        make_acyclic:bool,
+        /// Write the acyclic graph as graphml (needs --make-acyclic)
+        #[arg(long, requires = "make_acyclic")]
+        output:Option<PathBuf>
+    },
+    /// Trace the main path of knowledge flow through the citations and plot it
+    MainPath{
+        #[command(flatten)]
+        input:InputArgs,
+        #[command(flatten)]
+        plot:PlotArgs,
+        /// Search path count used to weight the citations
+        #[arg(long, value_enum, default_value_t = WeightBy::Spc)]
+        weight:WeightBy,
+        /// Use the heaviest overall path instead of greedily following the heaviest citations
+        #[arg(long)]
+        global:bool,
+        /// Remove a small set of citations first if the graph has cycles
+        #[arg(long)]
+        make_acyclic:bool,
+        /// Output png
+        #[arg(long, default_value = "plots/main_path.png")]
+        output:PathBuf,
+        /// Also write the path as graphml
+        #[arg(long)]
+        graphml:Option<PathBuf>
+    },
+    /// Link papers that share references (or, with --co-citation, that are cited together) and plot the components
+    Coupling{
+        #[command(flatten)]
+        input:InputArgs,
+        #[command(flatten)]
+        plot:PlotArgs,
+        /// Build the co-citation graph instead of the bibliographic coupling graph
+        #[arg(long)]
+        co_citation:bool,
+        /// Drop links with fewer shared references (or co-citing papers) than this
+        #[arg(long, default_value_t = 1)]
+        min_weight:usize,
+        /// Output png
+        #[arg(long, default_value = "plots/coupling.png")]
+        output:PathBuf,
+        /// Also write the weighted links as a source, target, weight csv
+        #[arg(long)]
+        links:Option<PathBuf>
+    },
+    /// Report the k-core decomposition of the whole graph and of every subject, plotting components against k
+    Cores{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Degree that has to reach k for a paper to stay in the core
+        #[arg(long, value_enum, default_value_t = CoreBy::Undirected)]
+        degree:CoreBy,
+        /// Also plot the components of this k-core to <output_dir>/k_core.png
+        #[arg(long)]
+        k:Option<usize>,
+        #[command(flatten)]
+        plot:PlotArgs,
+        /// Directory that receives core_profile.png and one <subject>_core_profile.png per subject
+        #[arg(long, default_value = "plots/cores")]
+        output_dir:PathBuf
+    },
+    /// List the papers and citations whose removal would split a component
+    Cuts{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Print at most this many articulation points and bridges
+        #[arg(long, default_value_t = 10)]
+        top:usize
+    },
+    /// Remove papers (and optionally citations) step by step and plot how the giant component shrinks
+    Robustness{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Removal orders to compare
+        #[arg(long, value_enum, value_delimiter = ',', default_values_t = [Removal::Random, Removal::Degree])]
+        strategies:Vec<Removal>,
+        /// Also remove citations in random order
+        #[arg(long)]
+        citations:bool,
+        /// Output png
+        #[arg(long, default_value = "plots/robustness.png")]
+        output:PathBuf
+    },
+    /// Stream new citations into the graph's components without recomputing them
+    Ingest{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Whitespace separated "citing cited" original ids, one citation per line
+        #[arg(long)]
+        feed:PathBuf,
+        /// Print the component summary after every this many citations
+        #[arg(long, default_value_t = 1000)]
+        every:usize,
+        /// Afterwards, report whether these two original ids ended up connected
+        #[arg(long, num_args = 2, value_names = ["A", "B"])]
+        same:Option<Vec<usize>>
+    },
+    /// Report duplicate papers and citations, self-citations and citations of missing papers
+    Quality{
+        #[command(flatten)]
+        input:InputArgs,
+        /// Also write the report as json
+        #[arg(long)]
+        output:Option<PathBuf>
+    },
+    /// Write the graph, with component ids and degree centrality, to another format
+    Export{
+        #[command(flatten)]
+        input:InputArgs,
+        #[arg(long, value_enum)]
+        format:ExportFormat,
+        /// Output file
+        #[arg(long)]
+        output:PathBuf,
+        /// Include the one-hot word features of every node
+        #[arg(long)]
+        features:bool,
+        /// Title of the html page
+        #[arg(long, default_value = "All Research Connected Components")]
+        title:String,
+        /// Relative size of the largest component circle in the html layout
+        #[arg(long, default_value_t = 3.0)]
+        biggest_circle:f64
+    }
+}
+
+#[derive(Args)]
+struct InputArgs{
+    /// Edge csv (index, sourceNodeId, targetNodeId, relationshipType)
+    #[arg(long, default_value = "citation_network/edges.csv")]
+    edges:PathBuf,
+    /// Node csv (index, nodeId, labels, subject, features)
+    #[arg(long, default_value = "citation_network/nodes.csv")]
+    nodes:PathBuf,
+    /// Read a single .graphml, .gexf, .mtx, binary cache (.ccg) or edge list (.txt/.edges) file instead of the csv pair
+    #[arg(long)]
+    input:Option<PathBuf>,
+    /// Treat an edge list input as undirected
+    #[arg(long)]
+    undirected:bool,
+    /// Binary cache of the csv pair, reused while both files are unchanged and rebuilt otherwise
+    #[arg(long)]
+    cache:Option<PathBuf>,
+    /// Validate the cache by hashing the csv contents instead of checking modification times
+    #[arg(long, requires = "cache")]
+    cache_hash:bool,
+    /// Keep only the first copy of a repeated citation
+    #[arg(long)]
+    dedupe:bool,
+    /// Drop citations of a paper to itself
+    #[arg(long)]
+    drop_self_loops:bool,
+    /// What to do with citations of papers missing from the nodes csv
+    #[arg(long, value_enum, conflicts_with_all = ["input", "cache"])]
+    missing_endpoints:Option<MissingBy>,
+    /// Load the csv pair in two streaming passes over the edge file, printing progress
+    #[arg(long, conflicts_with_all = ["input", "cache"])]
+    stream:bool
+}
+
+#[derive(Args)]
+struct PlotArgs{
+    /// Image width in pixels
+    #[arg(long, default_value_t = 1024)]
+    width:u32,
+    /// Image height in pixels
+    #[arg(long, default_value_t = 1024)]
+    height:u32,
+    /// The biggest circle takes up 1/biggest_circle of the plot
+    #[arg(long, default_value_t = 3.0)]
+    biggest_circle:f64
+}
+
+#[derive(Clone,Copy,ValueEnum)]
+enum MissingBy{
+    /// Stop with an error
+    Fail,
+    /// Leave the citation out
+    Drop,
+    /// Leave the citation out and list it in the quality report
+    Collect,
+    /// Add the paper with subject Unknown
+    Create
+}
+
+#[derive(Clone,Copy,ValueEnum)]
+enum SizeBy{
+    Fixed,
+    InDegree,
+    OutDegree,
+    Centrality
+}
+
+#[derive(Clone,Copy,ValueEnum)]
+enum WeightBy{
+    /// Search path count: source to sink paths through the citation
+    Spc,
+    /// Search path link count: paths from any paper to a sink
+    Splc,
+    /// Search path node pair: paths between any two papers
+    Spnp
+}
+
+#[derive(Clone,Copy,ValueEnum)]
+enum CoreBy{
+    Undirected,
+    /// Citations received from inside the core
+    In,
+    /// References to papers inside the core
+    Out
+}
+
+#[derive(Clone,Copy,ValueEnum)]
+enum Removal{
+    Random,
+    /// Most connected papers first
+    Degree,
+    /// Highest degree centrality first
+    Centrality,
+    /// Highest undirected coreness first
+    Coreness
+}
+
+#[derive(Clone,Copy,ValueEnum)]
+enum ExportFormat{
+    Html,
+    Graphml,
+    Gexf,
+    Dot,
+    /// node_id, component_id, component_size, subject csv with components numbered by size
+    Assignments
+}
+
+fn load_graph(input:&InputArgs) -> Result<Graph,String>{
+    let (graph, report) = load_graph_cleaned(input, MissingEndpoints::Fail)?;
+    if !report.is_clean(){
+        eprintln!("{}", report.summary());
+    }
+    Ok(graph)
+}
+
+//Load the input and apply the cleaning flags. Csv files may be .gz or .zst compressed with the gzip and zstd
+//features. The missing endpoint policy only applies to a csv pair read without a cache, every other input
+//already has all of its papers.
+fn load_graph_cleaned(input:&InputArgs, missing_default:MissingEndpoints) -> Result<(Graph, DataQualityReport),String>{
+    let missing_endpoints = match input.missing_endpoints{
+        Some(MissingBy::Fail) => MissingEndpoints::Fail,
+        Some(MissingBy::Drop) => MissingEndpoints::Drop,
+        Some(MissingBy::Collect) => MissingEndpoints::Collect,
+        Some(MissingBy::Create) => MissingEndpoints::Create,
+        None => missing_default
+    };
+    let policy = CleaningPolicy{dedupe:input.dedupe, drop_self_loops:input.drop_self_loops, missing_endpoints};
+    let mut graph = match (&input.input, &input.cache){
+        (None, None) if input.stream => return Graph::from_csvs_streaming(&input.edges, &input.nodes, &policy, |progress| eprintln!(
+            "Pass {}/2: {} citations read ({:.1}%)", progress.pass, progress.edges, 100.0 * progress.fraction())),
+        (None, None) => return Graph::from_csvs_cleaned(&input.edges, &input.nodes, &policy),
+        (None, Some(cache)) => {
+            let validation = if input.cache_hash { CacheValidation::ContentHash } else { CacheValidation::ModifiedTime };
+            Graph::from_csvs_cached(&input.edges, &input.nodes, cache, validation)?
+        },
+        (Some(path), _) => {
+            let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+            match extension.as_str(){
+                "graphml" => Graph::from_graphml(path)?,
+                "gexf" => Graph::from_gexf(path)?,
+                "mtx" => Graph::from_matrix_market(path)?,
+                "ccg" => Graph::load_binary(path)?,
+                _ => Graph::from_edge_list(path, !input.undirected)?
+            }
+        }
+    };
+    let report = graph.clean(&policy);
+    Ok((graph, report))
+}
+
+fn create_parent_dir(path:&Path) -> std::io::Result<()>{
+    match path.parent(){
+        Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent),
+        _ => Ok(())
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    match Cli::parse().command{
+        Command::Components{input, output} => {
+            let graph = load_graph(&input)?;
+            let (component,num_components) = graph.connected_components();
+            let component_sizes = count_components(&component, num_components);
+            let component_scale = get_component_scale(&component, num_components,true);
+
+            println!("Component Sizes: {:?}",component_sizes);
+            println!("Component Scale: {:?}",component_scale);
+            for summary in summarize_components(&graph, &component, num_components){
+                let approx = if summary.exact_distances { "" } else { "~" };
+                println!("Component {}: {} papers, {} citations, density {:.4}, diameter {}{}, radius {}{}, mostly {} (entropy {:.2} bits), most cited paper {} ({} citations)",
+                    summary.component, summary.size, summary.edges, summary.density,
+                    approx, summary.diameter, approx, summary.radius,
+                    summary.dominant_subject, summary.subject_entropy, summary.most_cited, summary.most_cited_count);
+            }
+            //Plot the component effectiveness
+            create_parent_dir(&output)?;
+            show_aggregation(&component_scale, &output)?;
+        },
+        Command::Subgraphs{input, plot, output_dir} => {
+            let graph = load_graph(&input)?;
+            std::fs::create_dir_all(&output_dir)?;
+            let subgraphs = graph.calculate_subgraphs();
+            //Subjects are analysed and plotted independently, on the rayon pool with the `parallel` feature.
+            //Errors are turned into strings because Box<dyn Error> can't cross threads.
+            #[cfg(feature = "parallel")]
+            let subjects = subgraphs.par_iter();
+            #[cfg(not(feature = "parallel"))]
+            let subjects = subgraphs.iter();
+            let results: Vec<Result<String,String>> = subjects.map(|(subject, subgraph)| {
+                let (component, num_components) = subgraph.connected_components();
+                let component_scale = get_component_scale(&component, num_components, true);
+                subgraph.visualize_connectivity(
+                    output_dir.join(format!("{}_connectivity.png",subject)),
+                    plot.biggest_circle, (plot.width,plot.height),
+                    &format!("Connectivity of Research Papers in {}",subject)).map_err(|e| e.to_string())?;
+                Ok(format!("Papers in {} have {} components. {:.2} of the data is captured in one component.",subject,num_components,component_scale[1]))
+            }).collect();
+            for result in results{
+                println!("{}", result?);
+            }
+        },
+        Command::Visualize{input, plot, output, title, size_by, max_edges, no_arrows, highlight_cuts} => {
+            let graph = load_graph(&input)?;
+            let (component,num_components) = graph.connected_components();
+            //REALLY cool custom visual that shows the connectivity of the graph components
+            let node_sizing = match size_by{
+                SizeBy::Fixed => NodeSizing::Fixed(5),
+                SizeBy::InDegree => NodeSizing::InDegree,
+                SizeBy::OutDegree => NodeSizing::OutDegree,
+                SizeBy::Centrality => NodeSizing::Metric(graph.degree_centrality())
+            };
+            let mut style = DrawStyle{node_sizing, arrows:!no_arrows, max_edges, ..DrawStyle::default()};
+            if highlight_cuts{
+                let cuts = graph.cut_structure();
+                let mapped = |id:&usize| graph.node_data()[id].mapped_node;
+                style.highlight_nodes = cuts.articulation_points.iter().map(|cut| mapped(&cut.node)).collect();
+                style.highlight_edges = cuts.bridges.iter().map(|(v, w)| (mapped(v), mapped(w))).collect();
+            }
+            //Reuse the components computed above instead of running the BFS again inside the plot
+            let positions = layout_components(&component, num_components, DRAWING_AREA, plot.biggest_circle);
+            create_parent_dir(&output)?;
+            let timings = graph.render_connectivity(&component, num_components, &positions,
+                &output, (plot.width,plot.height), &title, &style)?;
+            println!("Rendered {} edges ({} sampled out) in {:?}: setup {:?}, edges {:?}, nodes {:?}",
+                timings.edges_drawn, timings.edges_skipped, timings.total, timings.setup, timings.edges, timings.nodes);
+        },
+        Command::Stats{input, json, csv, path_samples} => {
+            let graph = load_graph(&input)?;
+            let report = AnalysisReport::from_graph(&graph);
+            println!("Nodes: {}", report.nodes);
+            println!("Edges: {}", report.edges);
+            println!("Components: {}", report.num_components);
+            println!("Largest component share: {:.4}", report.largest_component_share());
+            println!("Triangles: {}, transitivity {:.4}, average clustering {:.4}", report.triangles, report.transitivity, report.average_clustering);
+            for stats in report.subjects.iter(){
+                println!("Papers in {} have {} components. {:.2} of the data is captured in one component.",
+                    stats.subject, stats.num_components, stats.largest_component_share);
+                println!("    {} triangles, transitivity {:.4}, average clustering {:.4}",
+                    stats.triangles, stats.transitivity, stats.average_clustering);
+            }
+            if let Some(samples) = path_samples{
+                let distances = graph.sample_distances(samples, Direction::Undirected);
+                println!("Average path length: {:.3} over {} connected pairs from {} sources (longest {})",
+                    distances.average_path_length, distances.reachable_pairs, distances.sources, distances.max_distance());
+            }
+            if let Some(path) = json{
+                create_parent_dir(&path)?;
+                report.write_json(&path)?;
+            }
+            if let Some(path) = csv{
+                create_parent_dir(&path)?;
+                report.write_csv(&path)?;
+            }
+        },
+        Command::Chain{input, from, to, either_direction} => {
+            let graph = load_graph(&input)?;
+            let direction = if either_direction { Direction::Undirected } else { Direction::Directed };
+            match graph.citation_chain(from, to, direction)?{
+                Some(chain) => {
+                    let chain: Vec<String> = chain.iter().map(|id| id.to_string()).collect();
+                    println!("{} ({} steps)", chain.join(" -> "), chain.len() - 1);
+                },
+                None => println!("No citation chain links {} to {}", from, to)
+            }
+        },
+        Command::Cycles{input, max_cycles, make_acyclic, output} => {
+            let graph = load_graph(&input)?;
+            if graph.is_dag(){
+                println!("The citation graph is acyclic");
+            }
+            else{
+                let cyclic = graph.cyclic_components();
+                let papers: usize = cyclic.iter().map(|component| component.members.len()).sum();
+                println!("{} strongly connected components with cycles cover {} papers", cyclic.len(), papers);
+                for component in cyclic.iter().take(max_cycles){
+                    let cycle: Vec<String> = component.example_cycle.iter().map(|id| id.to_string()).collect();
+                    println!("{} papers, for example {}", component.members.len(), cycle.join(" -> "));
+                }
+            }
+            if make_acyclic{
+                let (acyclic, removed) = graph.make_acyclic();
+                println!("Removed {} citations:", removed.len());
+                for (citing, cited) in removed.iter(){
+                    println!("{} -> {}", citing, cited);
+                }
+                if let Some(path) = output{
+                    create_parent_dir(&path)?;
+                    write_graphml(&acyclic, &ExportOptions::default(), &path)?;
+                }
+            }
+        },
+        Command::MainPath{input, plot, weight, global, make_acyclic, output, graphml} => {
+            let mut graph = load_graph(&input)?;
+            if make_acyclic{
+                let (acyclic, removed) = graph.make_acyclic();
+                println!("Removed {} citations to break cycles", removed.len());
+                graph = acyclic;
+            }
+            let weight = match weight{
+                WeightBy::Spc => TraversalWeight::Spc,
+                WeightBy::Splc => TraversalWeight::Splc,
+                WeightBy::Spnp => TraversalWeight::Spnp
+            };
+            let search = if global { MainPathSearch::Global } else { MainPathSearch::Local };
+            let main_path = graph.main_path(weight, search)?;
+            println!("Main path of {} papers and {} citations, total weight {}", main_path.graph.n(), main_path.citations.len(), main_path.total_weight);
+            for (citing, cited, weight) in main_path.citations.iter(){
+                println!("{} -> {} ({})", citing, cited, weight);
+            }
+            create_parent_dir(&output)?;
+            main_path.graph.visualize_connectivity(&output, plot.biggest_circle, (plot.width,plot.height), "Main Path")?;
+            if let Some(path) = graphml{
+                create_parent_dir(&path)?;
+                write_graphml(&main_path.graph, &ExportOptions::default(), &path)?;
+            }
+        },
+        Command::Coupling{input, plot, co_citation, min_weight, output, links} => {
+            let graph = load_graph(&input)?;
+            let (derived, name) = if co_citation{
+                (graph.co_citation(min_weight), "Co-citation")
+            }
+            else{
+                (graph.bibliographic_coupling(min_weight), "Bibliographic Coupling")
+            };
+            let (component, num_components) = derived.graph.connected_components();
+            let component_scale = get_component_scale(&component, num_components, true);
+            println!("{} graph: {} links of weight {} or more, {} components. {:.2} of the data is captured in one component.",
+                name, derived.num_links(), min_weight, num_components, component_scale[1]);
+            create_parent_dir(&output)?;
+            derived.graph.visualize_connectivity(&output, plot.biggest_circle, (plot.width,plot.height), &format!("{} Connected Components", name))?;
+            if let Some(path) = links{
+                create_parent_dir(&path)?;
+                derived.write_links(&path)?;
+            }
+        },
+        Command::Cores{input, degree, k, plot, output_dir} => {
+            let graph = load_graph(&input)?;
+            let degree = match degree{
+                CoreBy::Undirected => CoreDegree::Undirected,
+                CoreBy::In => CoreDegree::In,
+                CoreBy::Out => CoreDegree::Out
+            };
+            std::fs::create_dir_all(&output_dir)?;
+            let mut profiles = vec![(String::from("All Research"), graph.core_profile(degree), output_dir.join("core_profile.png"))];
+            for (subject, subgraph) in graph.calculate_subgraphs(){
+                let path = output_dir.join(format!("{}_core_profile.png", subject));
+                profiles.push((subject, subgraph.core_profile(degree), path));
+            }
+            for (name, profile, path) in profiles.iter(){
+                if let Some(densest) = profile.last(){
+                    println!("{}: {}-core of {} papers in {} components, {:.2} in the largest",
+                        name, densest.k, densest.nodes, densest.num_components, densest.giant_share);
+                }
+                show_core_profile(profile, path, &format!("k-core Profile of {}", name))?;
+            }
+            if let Some(k) = k{
+                let core = graph.k_core(k, degree);
+                println!("The {}-core holds {} papers and {} citations", k, core.n(), core.calc_num_edges());
+                core.visualize_connectivity(output_dir.join("k_core.png"), plot.biggest_circle, (plot.width,plot.height), &format!("{}-core Connected Components", k))?;
+            }
+        },
+        Command::Cuts{input, top} => {
+            let graph = load_graph(&input)?;
+            let cuts = graph.cut_structure();
+            println!("{} articulation points and {} bridges", cuts.articulation_points.len(), cuts.bridges.len());
+            for cut in cuts.articulation_points.iter().take(top){
+                println!("Removing paper {} leaves {} pieces of sizes {:?}", cut.node, cut.num_pieces(), cut.piece_sizes);
+            }
+            for (v, w) in cuts.bridges.iter().take(top){
+                println!("Bridge {} - {}", v, w);
+            }
+        },
+        Command::Robustness{input, strategies, citations, output} => {
+            let graph = load_graph(&input)?;
+            let mut curves: Vec<(String, Vec<RobustnessPoint>)> = strategies.iter().map(|removal| {
+                let (name, strategy) = match removal{
+                    Removal::Random => ("Random papers", RemovalStrategy::Random),
+                    Removal::Degree => ("Highest degree", RemovalStrategy::Degree),
+                    Removal::Centrality => ("Highest centrality", RemovalStrategy::Ranking(graph.degree_centrality())),
+                    Removal::Coreness => ("Highest coreness", RemovalStrategy::Ranking(
+                        graph.coreness(CoreDegree::Undirected).iter().map(|core| *core as f64).collect()))
+                };
+                (String::from(name), graph.node_robustness(&strategy))
+            }).collect();
+            if citations{
+                curves.push((String::from("Random citations"), graph.edge_robustness()));
+            }
+            for (name, points) in curves.iter(){
+                //First step where the giant component holds less than half of the papers
+                let half = points.iter().find(|point| point.giant_share < 0.5);
+                match half{
+                    Some(point) => println!("{}: giant component drops below half after removing {:.1}% ({})",
+                        name, point.fraction_removed * 100.0, point.removed),
+                    None => println!("{}: giant component never held half of the papers", name)
+                }
+            }
+            create_parent_dir(&output)?;
+            let named: Vec<(&str, &[RobustnessPoint])> = curves.iter().map(|(name, points)| (name.as_str(), points.as_slice())).collect();
+            show_robustness(&named, &output)?;
+        },
+        Command::Ingest{input, feed, every, same} => {
+            let graph = load_graph(&input)?;
+            let mut connectivity = IncrementalConnectivity::from_graph(&graph);
+            let summary = |count:usize, connectivity:&IncrementalConnectivity| println!(
+                "{} citations ingested: {} papers in {} components, the largest holding {} ({:.4})",
+                count, connectivity.num_nodes(), connectivity.num_components(),
+                connectivity.largest_component_size(), connectivity.largest_component_share());
+            summary(0, &connectivity);
+            let mut count = 0;
+            for citation in edge_feed(&feed)?{
+                let (citing, cited) = citation?;
+                connectivity.add_edge(citing, cited);
+                count += 1;
+                if every > 0 && count % every == 0{
+                    summary(count, &connectivity);
+                }
+            }
+            if every == 0 || count % every != 0{
+                summary(count, &connectivity);
+            }
+            let sizes = connectivity.component_sizes();
+            println!("Largest component sizes: {:?}", &sizes[..sizes.len().min(10)]);
+            if let Some(pair) = same{
+                let (a, b) = (pair[0], pair[1]);
+                let size = |id:usize, connectivity:&mut IncrementalConnectivity| connectivity.component_size(id)
+                    .map(|size| format!("{} papers", size))
+                    .unwrap_or_else(|| String::from("unknown"));
+                println!("{} and {} are {}connected (components: {} and {})",
+                    a, b, if connectivity.same_component(a, b) { "" } else { "not " },
+                    size(a, &mut connectivity), size(b, &mut connectivity));
+            }
+        },
+        Command::Quality{input, output} => {
+            let (_, report) = load_graph_cleaned(&input, MissingEndpoints::Collect)?;
+            println!("{}", report.summary());
+            if report.is_clean(){
+                println!("No problems found");
+            }
+            if let Some(path) = output{
+                create_parent_dir(&path)?;
+                report.write_json(&path)?;
+            }
+        },
+        Command::Export{input, format, output, features, title, biggest_circle} => {
+            let graph = load_graph(&input)?;
+            let (component,num_components) = graph.connected_components();
+            create_parent_dir(&output)?;
+            //Files for Gephi and Graphviz, with the computed components and centrality attached
+            let centrality = graph.degree_centrality();
+            let export_options = ExportOptions{
+                include_features:features,
+                components:Some(&component),
+                node_metrics:vec![("degree_centrality", &centrality)],
+                ..ExportOptions::default()
+            };
+            match format{
+                ExportFormat::Html => {
+                    //Same layout as the png, but hoverable in a browser
+                    let positions = layout_components(&component, num_components, DRAWING_AREA, biggest_circle);
+                    write_html(&graph, &component, num_components, &positions, &output, &title)?
+                },
+                ExportFormat::Graphml => write_graphml(&graph, &export_options, &output)?,
+                ExportFormat::Gexf => write_gexf(&graph, &export_options, &output)?,
+                ExportFormat::Dot => write_dot(&graph, &export_options, &output)?,
+                ExportFormat::Assignments => write_component_assignments(&graph, &component, num_components, &output)?
+            }
+        }
     }
+    Ok(())
 }